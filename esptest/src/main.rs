@@ -22,15 +22,17 @@ use esp_hal::{main, Blocking};
 use iris_ui::button::make_button;
 use iris_ui::geom::{Bounds, Insets, Point as GPoint, Size as GSize};
 use iris_ui::gfx::TextStyle;
+use iris_ui::input::{dispatch_all, TouchCalibration, TouchInput};
 use iris_ui::label::make_label;
 use iris_ui::scene::pick_at;
 use iris_ui::scene::Scene;
-use iris_ui::scene::{click_at, draw_scene};
+use iris_ui::scene::draw_scene;
 use iris_ui::text_input::make_text_input;
 use iris_ui::view::{Align, Flex, View, ViewId};
 use iris_ui::Action;
 use iris_ui::EventType;
 use iris_ui::GuiEvent;
+use iris_ui::Handler;
 use iris_ui::Theme;
 use log::info;
 
@@ -126,6 +128,10 @@ fn main() -> ! {
         selected_bg: Rgb565::WHITE,
         selected_fg: Rgb565::BLACK,
         panel_bg: Rgb565::CSS_LIGHT_GRAY,
+        disabled_bg: Rgb565::CSS_LIGHT_GRAY,
+        disabled_fg: Rgb565::CSS_GRAY,
+        pressed_bg: Rgb565::CSS_GRAY,
+        hover_bg: Rgb565::CSS_LIGHT_GRAY,
         font: FONT_6X10,
         bold_font: FONT_7X13_BOLD,
     };
@@ -146,17 +152,32 @@ fn main() -> ! {
 
     let touch = Gt911Blocking::default();
     touch.init(i2c_ref).unwrap();
+    let mut touch_input = TouchInput::new(
+        || {
+            touch
+                .get_touch(i2c_ref)
+                .ok()
+                .flatten()
+                .map(|point| (point.x as i32, point.y as i32))
+        },
+        // flip because the screen is mounted sideways on the t-deck
+        TouchCalibration {
+            raw_min: GPoint::new(0, 0),
+            raw_max: GPoint::new(320, 240),
+            width: 321,
+            height: 241,
+            swap_xy: true,
+            invert_x: true,
+            invert_y: true,
+        },
+    );
+    let boot = Instant::now();
 
     loop {
         // handle touch inputs
-        if let Ok(point) = touch.get_touch(i2c_ref) {
-            if let Some(point) = point {
-                // flip because the screen is mounted sideways on the t-deck
-                let pt = GPoint::new(320 - point.y as i32, 240 - point.x as i32);
-                if let Some(result) = click_at(&mut scene, &vec![], pt) {
-                    info!("view returned result {result:?}");
-                }
-            }
+        let now = boot.elapsed().as_millis();
+        for result in dispatch_all(&mut scene, &vec![], &mut touch_input, now, &mut ()) {
+            info!("view returned result {result:?}");
         }
 
         let delay_start = Instant::now();
@@ -172,11 +193,11 @@ fn make_gui_scene() -> Scene {
     let panel = View {
         name: ViewId::new("panel"),
         bounds: Bounds::new(20, 20, 200, 200),
-        draw: Some(draw_std_panel),
+        draw: Some(Handler::Fn(draw_std_panel)),
         padding: Insets::new_same(5),
         h_flex: Flex::Resize,
         v_flex: Flex::Resize,
-        layout: Some(layout_hbox),
+        layout: Some(Handler::Fn(layout_hbox)),
         ..Default::default()
     };
 
@@ -220,7 +241,7 @@ fn make_menuview(name: &'static str, data: Vec<String>) -> View {
         title: name.into(),
         bounds: Bounds::new(0, 0, 100, data.len() as i32 * VH),
         visible: true,
-        draw: Some(|e| {
+        draw: Some(Handler::Fn(|e| {
             e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
             if let Some(state) = &e.view.state {
                 if let Some(state) = state.downcast_ref::<MenuState>() {
@@ -257,8 +278,8 @@ fn make_menuview(name: &'static str, data: Vec<String>) -> View {
                 }
             }
             e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
-        }),
-        input: Some(|event| {
+        })),
+        input: Some(Handler::Fn(|event| {
             // info!("menu clicked at");
             match &event.event_type {
                 EventType::Tap(pt) => {
@@ -276,7 +297,7 @@ fn make_menuview(name: &'static str, data: Vec<String>) -> View {
                                         state.selected = selected as usize;
                                         info!("menu state is {:?}", state.selected);
                                         event.scene.set_focused(&name);
-                                        return Some(Action::Command("selected".into()));
+                                        return Some(Action::Selected(state.selected));
                                     }
                                 }
                             }
@@ -288,8 +309,8 @@ fn make_menuview(name: &'static str, data: Vec<String>) -> View {
                 }
             }
             None
-        }),
-        layout: Some(|event| {
+        })),
+        layout: Some(Handler::Fn(|event| {
             if let Some(parent) = event.scene.get_view_mut(event.target) {
                 if let Some(state) = &parent.state {
                     if let Some(state) = state.downcast_ref::<MenuState>() {
@@ -297,7 +318,7 @@ fn make_menuview(name: &'static str, data: Vec<String>) -> View {
                     }
                 }
             };
-        }),
+        })),
         state: Some(Box::new(MenuState { data, selected: 0 })),
         ..Default::default()
     }
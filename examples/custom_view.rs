@@ -3,18 +3,16 @@ use embedded_graphics::mono_font::ascii::FONT_7X13_BOLD;
 use embedded_graphics::mono_font::iso_8859_9::FONT_7X13;
 use embedded_graphics::pixelcolor::{Rgb565, WebColors};
 use embedded_graphics::prelude::RgbColor;
-use embedded_graphics_simulator::{
-    OutputSettingsBuilder, SimulatorDisplay, Window,
-};
+use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay, Window};
 use env_logger::Target;
 use iris_ui::device::EmbeddedDrawingContext;
 use iris_ui::geom::{Bounds, Size};
-use iris_ui::scene::{draw_scene, layout_scene, Scene};
+use iris_ui::scene::{Scene, draw_scene, layout_scene};
 use iris_ui::view::{View, ViewId};
-use iris_ui::Theme;
+use iris_ui::{EventType, Handler, Theme};
 use log::LevelFilter;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 // struct for the state of the progress bar
 struct ProgressState {
@@ -29,18 +27,29 @@ fn make_progress_bar(name: &ViewId) -> View {
         // set the state
         state: Some(Box::new(ProgressState { value: 0.0 })),
 
-        // no input
-        input: None,
+        // advance on every timer tick
+        input: Some(Handler::Fn(|e| {
+            if let EventType::Timer(_) = e.event_type {
+                if let Some(state) = e.scene.get_view_state::<ProgressState>(e.target) {
+                    state.value += 0.01;
+                    if state.value > 1.0 {
+                        state.value = 0.0;
+                    }
+                }
+                e.scene.mark_dirty_view(e.target);
+            }
+            None
+        })),
 
         // fixed size layout
-        layout: Some(|e| {
+        layout: Some(Handler::Fn(|e| {
             if let Some(view) = e.scene.get_view_mut(e.target) {
                 view.bounds.size = Size::new(100, 20);
             }
-        }),
+        })),
 
         // draw progress bar
-        draw: Some(|e| {
+        draw: Some(Handler::Fn(|e| {
             e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
             let full = e.view.bounds.size;
             // get the state to calculate the fill width
@@ -50,7 +59,7 @@ fn make_progress_bar(name: &ViewId) -> View {
                 e.ctx.fill_rect(&bd2, &e.theme.selected_bg);
             }
             e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
-        }),
+        })),
 
         ..Default::default()
     }
@@ -68,6 +77,8 @@ fn main() -> Result<(), std::convert::Infallible> {
 
     let mut scene = Scene::new_with_bounds(Bounds::new(0, 0, 320, 240));
     scene.add_view_to_root(make_progress_bar(&progress_id));
+    // update the progress bar every 100 msec
+    scene.start_timer(&progress_id, 100, true);
 
     // let mut scene = make_vbox_test();
     let mut theme = Theme {
@@ -76,12 +87,17 @@ fn main() -> Result<(), std::convert::Infallible> {
         selected_bg: Rgb565::BLUE,
         selected_fg: Rgb565::WHITE,
         panel_bg: Rgb565::CSS_LIGHT_GRAY,
+        disabled_bg: Rgb565::CSS_LIGHT_GRAY,
+        disabled_fg: Rgb565::CSS_GRAY,
+        pressed_bg: Rgb565::CSS_GRAY,
+        hover_bg: Rgb565::CSS_LIGHT_GRAY,
         font: FONT_7X13,
         bold_font: FONT_7X13_BOLD,
     };
 
     let output_settings = OutputSettingsBuilder::new().scale(2).build();
     let mut window = Window::new("Simulator Test", &output_settings);
+    let start = Instant::now();
     'running: loop {
         let mut ctx = EmbeddedDrawingContext::new(&mut display);
         ctx.clip = scene.dirty_rect.clone();
@@ -103,14 +119,11 @@ fn main() -> Result<(), std::convert::Infallible> {
         //     }
         // }
 
-        // update the progress bar every 100 msec
-        if let Some(state) = scene.get_view_state::<ProgressState>(&progress_id) {
-            state.value += 0.01;
-            if state.value > 1.0 {
-                state.value = 0.0;
-            }
-            scene.mark_dirty_view(&progress_id);
-            sleep(Duration::from_millis(100));
+        // deliver due timers, then sleep until the next one
+        let now = start.elapsed().as_millis() as u64;
+        scene.tick(now, &mut ());
+        if let Some(deadline) = scene.next_deadline() {
+            sleep(Duration::from_millis(deadline.saturating_sub(now)));
         }
     }
     Ok(())
@@ -11,28 +11,29 @@ use embedded_graphics::prelude::Primitive;
 use embedded_graphics::prelude::RgbColor;
 use embedded_graphics::prelude::WebColors;
 use embedded_graphics::primitives::{Line, PrimitiveStyle, Rectangle};
+use iris_ui::accelerator::{AcceleratorPriority, AcceleratorTarget, KeyCombo};
+use iris_ui::animation::{AnimationTarget, Easing};
 use iris_ui::button::make_button;
-use iris_ui::geom::{Bounds, Insets, Point as GPoint};
-use iris_ui::scene::{EventResult, Scene, click_at, draw_scene, event_at_focused, layout_scene};
+use iris_ui::geom::{Bounds, Insets, Point};
+use iris_ui::scene::{EventResult, Scene, draw_scene, layout_scene};
 use iris_ui::toggle_button::make_toggle_button;
 use iris_ui::toggle_group::{SelectOneOfState, layout_toggle_group, make_toggle_group};
-use iris_ui::{Action, EventType, KeyboardAction, Theme, util};
+use iris_ui::{Action, Handler, InputHandler, Key, Theme};
 use std::convert::Into;
+use std::time::Instant;
 
 use embedded_graphics::prelude::*;
-use embedded_graphics_simulator::sdl2::{Keycode, Mod};
-use embedded_graphics_simulator::{
-    OutputSettingsBuilder, SimulatorDisplay, SimulatorEvent, Window,
-};
+use embedded_graphics_simulator::{OutputSettingsBuilder, SimulatorDisplay, Window};
 use env_logger::Target;
 use env_logger::fmt::style::Color::Rgb;
 use iris_ui::device::EmbeddedDrawingContext;
 use iris_ui::grid::{GridLayoutState, LayoutConstraint, make_grid_panel};
+use iris_ui::input::{InputSource, SimulatorInput, dispatch_input};
 use iris_ui::label::make_label;
 use iris_ui::layouts::{layout_hbox, layout_std_panel, layout_vbox};
 use iris_ui::list_view::make_list_view;
 use iris_ui::panel::draw_std_panel;
-use iris_ui::tabbed_panel::{LayoutPanelState, make_tabbed_panel};
+use iris_ui::tabbed_panel::make_tabbed_panel;
 use iris_ui::text_input::make_text_input;
 use iris_ui::util::hex_str_to_rgb565;
 use iris_ui::view::Align::{Center, Start};
@@ -45,6 +46,7 @@ const MEDIUM_FONT_BUTTON: &'static ViewId = &ViewId::new("medium_font");
 const LARGE_FONT_BUTTON: &'static ViewId = &ViewId::new("large_font");
 
 const TABBED_PANEL: &'static ViewId = &ViewId::new("tabbed-panel");
+const TABS: &'static ViewId = &ViewId::new("tabs");
 const BUTTONS_PANEL: &'static ViewId = &ViewId::new("buttons");
 const LAYOUT_PANEL: &'static ViewId = &ViewId::new("layout-panel");
 const LISTS_PANEL: &'static ViewId = &ViewId::new("lists-panel");
@@ -53,18 +55,17 @@ const THEMES_PANEL: &'static ViewId = &ViewId::new("themes-panel");
 
 const POPUP_BUTTON: &'static ViewId = &ViewId::new("list-button");
 const POPUP_MENU: &'static ViewId = &ViewId::new("popup-menu");
-fn make_scene() -> Scene {
+fn make_scene() -> Scene<Theme> {
     let mut scene = Scene::new_with_bounds(Bounds::new(0, 0, 320, 240));
 
-    let tab_names = vec!["buttons", "layouts", "lists", "inputs", "themes"];
-    let mut tabbed_panel: View = make_tabbed_panel(&TABBED_PANEL, tab_names, 0, &mut scene);
-    if let Some(state) = tabbed_panel.get_state::<LayoutPanelState>() {
-        state.register_panel("buttons", BUTTONS_PANEL);
-        state.register_panel("layouts", LAYOUT_PANEL);
-        state.register_panel("lists", LISTS_PANEL);
-        state.register_panel("inputs", INPUTS_PANEL);
-        state.register_panel("themes", THEMES_PANEL);
-    }
+    let tabs = vec![
+        ("buttons", *BUTTONS_PANEL),
+        ("layouts", *LAYOUT_PANEL),
+        ("lists", *LISTS_PANEL),
+        ("inputs", *INPUTS_PANEL),
+        ("themes", *THEMES_PANEL),
+    ];
+    let tabbed_panel: View<Theme> = make_tabbed_panel(TABBED_PANEL, TABS, tabs, 0, &mut scene);
 
     {
         let mut grid = make_grid_panel(BUTTONS_PANEL);
@@ -107,11 +108,11 @@ fn make_scene() -> Scene {
     {
         let mut wrapper = View {
             name: LAYOUT_PANEL.clone(),
-            draw: Some(draw_std_panel),
+            draw: Some(Handler::Fn(draw_std_panel)),
             padding: Insets::new_same(5),
             h_flex: Flex::Resize,
             v_flex: Flex::Resize,
-            layout: Some(layout_hbox),
+            layout: Some(Handler::Fn(layout_hbox)),
             ..Default::default()
         };
 
@@ -120,8 +121,8 @@ fn make_scene() -> Scene {
             scene.add_view_to_parent(make_label("vbox-label", "vbox layout"), &col1.name);
             let vbox = View {
                 name: ViewId::new("vbox"),
-                draw: Some(draw_std_panel),
-                layout: Some(layout_vbox),
+                draw: Some(Handler::Fn(draw_std_panel)),
+                layout: Some(Handler::Fn(layout_vbox)),
                 ..Default::default()
             };
             scene.add_view_to_parent(make_button(&ViewId::new("vbox-button1"), "A"), &vbox.name);
@@ -148,8 +149,8 @@ fn make_scene() -> Scene {
     {
         let mut wrapper = View {
             name: LISTS_PANEL.clone(),
-            layout: Some(layout_hbox),
-            draw: Some(draw_std_panel),
+            layout: Some(Handler::Fn(layout_hbox)),
+            draw: Some(Handler::Fn(draw_std_panel)),
             h_flex: Flex::Resize,
             v_flex: Flex::Resize,
             ..Default::default()
@@ -171,10 +172,10 @@ fn make_scene() -> Scene {
     {
         let mut panel = View {
             name: INPUTS_PANEL.clone(),
-            draw: Some(draw_std_panel),
+            draw: Some(Handler::Fn(draw_std_panel)),
             h_flex: Flex::Resize,
             v_flex: Flex::Resize,
-            layout: Some(layout_std_panel),
+            layout: Some(Handler::Fn(layout_std_panel)),
             ..Default::default()
         };
         scene.add_view_to_parent(
@@ -189,7 +190,7 @@ fn make_scene() -> Scene {
         let themes_list_id = ViewId::new("themes-list");
         let themes = make_list_view(
             &themes_list_id,
-            vec!["Light", "Dark", "Ice Cream", "Minty Fresh", "Amber"],
+            THEMES.iter().map(|(name, _)| *name).collect(),
             0,
         );
         scene.add_view_to_parent(themes, &panel.name);
@@ -202,28 +203,52 @@ fn make_scene() -> Scene {
         let mut font_buttons = View {
             name: ViewId::new("font_buttons"),
             bounds: Bounds::new(30, 200, 200, 30),
-            layout: Some(layout_hbox),
+            layout: Some(Handler::Fn(layout_hbox)),
             h_flex: Intrinsic,
             v_flex: Intrinsic,
-            draw: Some(draw_std_panel),
+            draw: Some(Handler::Fn(draw_std_panel)),
             ..Default::default()
         };
-        scene.add_view_to_parent(make_button(SMALL_FONT_BUTTON, "Small"), &font_buttons.name);
-        scene.add_view_to_parent(
-            make_button(MEDIUM_FONT_BUTTON, "Medium"),
-            &font_buttons.name,
-        );
-        scene.add_view_to_parent(make_button(LARGE_FONT_BUTTON, "Large"), &font_buttons.name);
+        for (id, title, font, bold_font) in [
+            (SMALL_FONT_BUTTON, "Small", FONT_5X7, FONT_5X7),
+            (MEDIUM_FONT_BUTTON, "Medium", FONT_6X10, FONT_6X10),
+            (LARGE_FONT_BUTTON, "Large", FONT_7X13, FONT_7X13_BOLD),
+        ] {
+            let mut button = make_button(id, title);
+            // update the theme passed as the app context directly
+            button.input = Some(InputHandler::<Theme>::closure(move |e| {
+                e.context.font = font;
+                e.context.bold_font = bold_font;
+                // every view redraws in the new font, even the ones that don't move
+                e.scene.mark_layout_dirty();
+                e.scene.mark_dirty_all();
+                Some(Action::Generic)
+            }));
+            scene.add_view_to_parent(button, &font_buttons.name);
+        }
         scene.add_view_to_root(font_buttons);
     }
 
+    // ctrl+1/2/3 press the font buttons no matter what is focused
+    for (ch, button) in [
+        ('1', SMALL_FONT_BUTTON),
+        ('2', MEDIUM_FONT_BUTTON),
+        ('3', LARGE_FONT_BUTTON),
+    ] {
+        scene.add_accelerator(
+            KeyCombo::ctrl(Key::Char(ch)),
+            AcceleratorTarget::View(*button),
+            AcceleratorPriority::BeforeFocused,
+        );
+    }
+
     if let Some(state) = scene.get_view_state::<SelectOneOfState>(TABBED_PANEL) {
         state.selected = 2;
     }
 
     scene
 }
-fn make_vbox_test() -> Scene {
+fn make_vbox_test() -> Scene<Theme> {
     let mut scene = Scene::new_with_bounds(Bounds::new(0, 0, 320, 240));
     let parent_id: ViewId = "parent".into();
     let parent_view = View {
@@ -235,8 +260,8 @@ fn make_vbox_test() -> Scene {
         v_flex: Resize,
         h_align: Start,
         v_align: Start,
-        layout: Some(layout_hbox),
-        draw: Some(draw_std_panel),
+        layout: Some(Handler::Fn(layout_hbox)),
+        draw: Some(Handler::Fn(draw_std_panel)),
         ..Default::default()
     };
     {
@@ -262,8 +287,8 @@ fn make_vbox_test() -> Scene {
     let child_box = View {
         name: ViewId::new("foo"),
         padding: Insets::new_same(5),
-        layout: Some(layout_vbox),
-        draw: Some(draw_std_panel),
+        layout: Some(Handler::Fn(layout_vbox)),
+        draw: Some(Handler::Fn(draw_std_panel)),
         bounds: Bounds::new(0, 0, 100, 100),
         h_flex: Intrinsic,
         v_flex: Resize,
@@ -298,7 +323,7 @@ fn make_vbox_test() -> Scene {
     //         title: "ch4".into(),
     //         h_flex: Flex::Resize,
     //         v_flex: Flex::Resize,
-    //         layout: Some(layout_std_panel),
+    //         layout: Some(Handler::Fn(layout_std_panel)),
     //         ..Default::default()
     //     },
     //     &parent_id,
@@ -308,26 +333,26 @@ fn make_vbox_test() -> Scene {
     scene
 }
 
-fn make_column(name: &'static str) -> View {
+fn make_column(name: &'static str) -> View<Theme> {
     View {
         name: ViewId::new(name),
-        draw: Some(draw_std_panel),
+        draw: Some(Handler::Fn(draw_std_panel)),
         h_flex: Resize,
         v_flex: Resize,
         h_align: Center,
         v_align: Start,
-        layout: Some(layout_vbox),
+        layout: Some(Handler::Fn(layout_vbox)),
         ..Default::default()
     }
 }
 
-fn make_row(name: &'static str) -> View {
+fn make_row(name: &'static str) -> View<Theme> {
     View {
         name: ViewId::new(name),
-        draw: Some(draw_std_panel),
+        draw: Some(Handler::Fn(draw_std_panel)),
         h_flex: Resize,
         v_flex: Resize,
-        layout: Some(layout_hbox),
+        layout: Some(Handler::Fn(layout_hbox)),
         ..Default::default()
     }
 }
@@ -348,140 +373,93 @@ fn main() -> Result<(), std::convert::Infallible> {
         selected_bg: Rgb565::BLUE,
         selected_fg: Rgb565::WHITE,
         panel_bg: Rgb565::CSS_LIGHT_GRAY,
+        disabled_bg: Rgb565::CSS_LIGHT_GRAY,
+        disabled_fg: Rgb565::CSS_GRAY,
+        pressed_bg: Rgb565::CSS_GRAY,
+        hover_bg: Rgb565::CSS_LIGHT_GRAY,
         font: FONT_7X13,
         bold_font: FONT_7X13_BOLD,
     };
     copy_theme_colors(&mut theme, &LIGHT_THEME);
 
+    // pass `--record <file>` to save the session for replaying in a test
+    let args: Vec<String> = std::env::args().collect();
+    let record_path = args
+        .iter()
+        .position(|arg| arg == "--record")
+        .and_then(|i| args.get(i + 1).cloned());
+    if record_path.is_some() {
+        scene.start_recording();
+    }
+
     let output_settings = OutputSettingsBuilder::new().scale(2).build();
     let mut window = Window::new("Simulator Test", &output_settings);
+    let start = Instant::now();
     'running: loop {
         let mut ctx = EmbeddedDrawingContext::new(&mut display);
         ctx.clip = scene.dirty_rect.clone();
         layout_scene(&mut scene, &theme);
         draw_scene(&mut scene, &mut ctx, &theme);
         window.update(&display);
-        for event in window.events() {
-            match event {
-                SimulatorEvent::Quit => break 'running,
-                SimulatorEvent::KeyDown {
-                    keycode, keymod, ..
-                } => {
-                    let evt: EventType = keydown_to_char(keycode, keymod);
-                    if let Some(result) = event_at_focused(&mut scene, &evt) {
-                        println!("got input from {:?}", result);
-                    }
-                }
-                SimulatorEvent::MouseButtonUp { point, .. } => {
-                    println!("mouse button up {}", point);
-                    if let Some(result) =
-                        click_at(&mut scene, &vec![], GPoint::new(point.x, point.y))
-                    {
-                        handle_events(result, &mut scene, &mut theme);
-                    }
-                }
-                SimulatorEvent::MouseButtonDown { mouse_btn, point } => {
-                    println!("mouse down");
-                }
-                SimulatorEvent::MouseWheel {
-                    scroll_delta,
-                    direction,
-                } => {
-                    info!("mouse wheel {scroll_delta:?} {direction:?}");
-                    if let Some(result) = event_at_focused(
-                        &mut scene,
-                        &EventType::Scroll(scroll_delta.x, scroll_delta.y),
-                    ) {
-                        println!("got input from {:?}", result);
-                    }
-                }
-                _ => {}
-            }
-        }
-    }
-    Ok(())
-}
-
-fn keydown_to_char(keycode: Keycode, keymod: Mod) -> EventType {
-    println!("keycode as number {}", keycode.into_i32());
-    let ch = keycode.into_i32();
-    if ch <= 0 {
-        return EventType::Unknown;
-    }
-    let shifted = keymod.contains(Mod::LSHIFTMOD) || keymod.contains(Mod::RSHIFTMOD);
-    let controlled = keymod.contains(Mod::LCTRLMOD) || keymod.contains(Mod::RCTRLMOD);
-
-    if let Some(ch) = char::from_u32(ch as u32) {
-        if ch == 'd' && controlled {
-            return EventType::KeyboardAction(KeyboardAction::Delete);
+        let now = start.elapsed().as_millis() as u64;
+        for result in scene.tick(now, &mut theme) {
+            handle_events(result, &mut scene, &mut theme);
         }
-        if ch.is_alphabetic() {
-            return if shifted {
-                EventType::Keyboard(ch.to_ascii_uppercase() as u8)
-            } else {
-                EventType::Keyboard(ch.to_ascii_lowercase() as u8)
-            };
+        let mut input = SimulatorInput::new(window.events());
+        while let Some(event) = input.poll(now) {
+            println!("input event {:?}", event);
+            if let Some(result) = dispatch_input(&mut scene, &vec![], event, &mut theme) {
+                handle_events(result, &mut scene, &mut theme);
+            }
         }
-        if ch.is_ascii_graphic() {
-            return EventType::Keyboard(ch as u8);
+        if input.quit {
+            break 'running;
         }
     }
-    match keycode {
-        Keycode::Backspace => EventType::KeyboardAction(KeyboardAction::Backspace),
-        Keycode::LEFT => EventType::KeyboardAction(KeyboardAction::Left),
-        Keycode::RIGHT => EventType::KeyboardAction(KeyboardAction::Right),
-        Keycode::UP => EventType::KeyboardAction(KeyboardAction::Up),
-        Keycode::DOWN => EventType::KeyboardAction(KeyboardAction::Down),
-        Keycode::SPACE => EventType::Keyboard(b' '),
-        _ => {
-            println!("not supported: {keycode}");
-            return EventType::Unknown;
+    if let (Some(path), Some(recording)) = (record_path, scene.stop_recording()) {
+        match std::fs::write(&path, recording.to_text()) {
+            Ok(()) => info!("saved {} events to {path}", recording.events.len()),
+            Err(err) => info!("could not save the recording to {path}: {err}"),
         }
     }
+    Ok(())
 }
 
-fn handle_events(result: EventResult, scene: &mut Scene, theme: &mut Theme) {
+fn handle_events(result: EventResult, scene: &mut Scene<Theme>, theme: &mut Theme) {
     let (name, action) = result;
     println!("result of event {:?} from {name}", action);
-    if name == *SMALL_FONT_BUTTON {
-        theme.font = FONT_5X7;
-        theme.bold_font = FONT_5X7;
-        scene.mark_layout_dirty();
-    }
-    if name == *MEDIUM_FONT_BUTTON {
-        theme.font = FONT_6X10;
-        theme.bold_font = FONT_6X10;
-        scene.mark_layout_dirty();
-    }
-    if name == *LARGE_FONT_BUTTON {
-        theme.font = FONT_7X13;
-        theme.bold_font = FONT_7X13_BOLD;
-        scene.mark_layout_dirty();
-    }
-    if name.as_str() == "themes-list" {
-        match &action {
-            Action::Generic => {}
-            Action::Command(cmd) => {
-                match cmd.as_str() {
-                    "Dark" => copy_theme_colors(theme, &DARK_THEME),
-                    "Light" => copy_theme_colors(theme, &LIGHT_THEME),
-                    "Ice Cream" => copy_theme_colors(theme, &ICE_CREAM_THEME),
-                    "Minty Fresh" => copy_theme_colors(theme, &MINTY_FRESH),
-                    "Amber" => copy_theme_colors(theme, &AMBER),
-                    _ => {}
-                }
-                scene.mark_dirty_all();
-            }
-        }
+    if name.as_str() == "themes-list"
+        && let Action::Selected(index) = action
+        && let Some((_, new_theme)) = THEMES.get(index)
+    {
+        copy_theme_colors(theme, new_theme);
+        scene.mark_dirty_all();
     }
     if name == *POPUP_BUTTON {
         let menu =
             make_list_view(POPUP_MENU, vec!["Item 1", "Item 2", "Item 3"], 0).position_at(50, 50);
+        // slide the popup up from the bottom of the screen
+        let to = menu.bounds;
+        let from = to + Point::new(0, 240 - to.position.y);
         scene.set_focused(&menu.name);
         scene.add_view_to_root(menu);
+        scene.animate(
+            POPUP_MENU,
+            AnimationTarget::Bounds(from, to),
+            250,
+            Easing::EaseOut,
+        );
+        // the popup gets its own shortcuts while it is open
+        scene.push_accelerator_scope(POPUP_MENU, true);
+        scene.add_accelerator(
+            KeyCombo::ctrl(Key::Char('w')),
+            AcceleratorTarget::Action(Action::Command("close".into())),
+            AcceleratorPriority::BeforeFocused,
+        );
     }
     if name == *POPUP_MENU {
         scene.remove_view(POPUP_MENU);
+        scene.pop_accelerator_scope();
     }
 }
 
@@ -491,6 +469,10 @@ const LIGHT_THEME: Theme = Theme {
     panel_bg: Rgb565::CSS_LIGHT_GRAY,
     selected_bg: hex_str_to_rgb565("#4488ff"),
     selected_fg: Rgb565::WHITE,
+    disabled_bg: hex_str_to_rgb565("#eeeeee"),
+    disabled_fg: hex_str_to_rgb565("#aaaaaa"),
+    pressed_bg: hex_str_to_rgb565("#bbccff"),
+    hover_bg: hex_str_to_rgb565("#e4ecff"),
     font: FONT_7X13,
     bold_font: FONT_7X13_BOLD,
 };
@@ -500,6 +482,10 @@ const DARK_THEME: Theme = Theme {
     panel_bg: Rgb565::BLACK,
     selected_bg: hex_str_to_rgb565("#000088"),
     selected_fg: hex_str_to_rgb565("#3366ff"),
+    disabled_bg: hex_str_to_rgb565("#1a1a1a"),
+    disabled_fg: hex_str_to_rgb565("#555555"),
+    pressed_bg: hex_str_to_rgb565("#444444"),
+    hover_bg: hex_str_to_rgb565("#333333"),
     font: FONT_7X13,
     bold_font: FONT_7X13_BOLD,
 };
@@ -511,6 +497,10 @@ const ICE_CREAM_THEME: Theme = Theme {
     panel_bg: hex_str_to_rgb565("fff6d3"),
     selected_bg: hex_str_to_rgb565("#eb6b6f"),
     selected_fg: hex_str_to_rgb565("#fff6d3"),
+    disabled_bg: hex_str_to_rgb565("#f2e6c8"),
+    disabled_fg: hex_str_to_rgb565("#bd9a95"),
+    pressed_bg: hex_str_to_rgb565("#f4a9a8"),
+    hover_bg: hex_str_to_rgb565("#fbe3cf"),
     font: FONT_7X13,
     bold_font: FONT_7X13_BOLD,
 };
@@ -521,6 +511,10 @@ const MINTY_FRESH: Theme = Theme {
     panel_bg: hex_str_to_rgb565("#40332f"),
     selected_bg: hex_str_to_rgb565("#95c798"),
     selected_fg: hex_str_to_rgb565("#40332f"),
+    disabled_bg: hex_str_to_rgb565("#5e4d3f"),
+    disabled_fg: hex_str_to_rgb565("#bdb49a"),
+    pressed_bg: hex_str_to_rgb565("#6d9e70"),
+    hover_bg: hex_str_to_rgb565("#9a8063"),
     font: FONT_7X13,
     bold_font: FONT_7X13_BOLD,
 };
@@ -531,14 +525,30 @@ const AMBER: Theme = Theme {
     panel_bg: hex_str_to_rgb565("#0d0405"),
     selected_bg: hex_str_to_rgb565("#fed018"),
     selected_fg: hex_str_to_rgb565("#5e1210"),
+    disabled_bg: hex_str_to_rgb565("#0d0405"),
+    disabled_fg: hex_str_to_rgb565("#6e2d03"),
+    pressed_bg: hex_str_to_rgb565("#5e1210"),
+    hover_bg: hex_str_to_rgb565("#2a0c06"),
     font: FONT_7X13,
     bold_font: FONT_7X13_BOLD,
 };
 
+const THEMES: [(&str, &Theme); 5] = [
+    ("Light", &LIGHT_THEME),
+    ("Dark", &DARK_THEME),
+    ("Ice Cream", &ICE_CREAM_THEME),
+    ("Minty Fresh", &MINTY_FRESH),
+    ("Amber", &AMBER),
+];
+
 fn copy_theme_colors(theme: &mut Theme, new: &Theme) {
     theme.bg = new.bg;
     theme.panel_bg = new.panel_bg;
     theme.selected_bg = new.selected_bg;
     theme.fg = new.fg;
     theme.selected_fg = new.selected_fg;
+    theme.disabled_bg = new.disabled_bg;
+    theme.disabled_fg = new.disabled_fg;
+    theme.pressed_bg = new.pressed_bg;
+    theme.hover_bg = new.hover_bg;
 }
@@ -0,0 +1,200 @@
+use crate::scene::Scene;
+use crate::view::ViewId;
+use crate::{Action, Key, KeyEvent, Modifiers};
+use alloc::vec;
+use alloc::vec::Vec;
+use log::warn;
+
+/// A key plus the exact set of modifiers that must be held, e.g. Ctrl+S.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombo {
+    pub key: Key,
+    pub modifiers: Modifiers,
+}
+
+impl KeyCombo {
+    pub const fn new(key: Key) -> KeyCombo {
+        KeyCombo {
+            key,
+            modifiers: Modifiers::NONE,
+        }
+    }
+    pub const fn ctrl(key: Key) -> KeyCombo {
+        KeyCombo {
+            key,
+            modifiers: Modifiers::ctrl(),
+        }
+    }
+    pub const fn with_modifiers(mut self, modifiers: Modifiers) -> KeyCombo {
+        self.modifiers = modifiers;
+        self
+    }
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        key.is_pressed() && key.key == self.key && key.modifiers == self.modifiers
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum AcceleratorTarget {
+    /// Emit the action as if the scope owner produced it.
+    Action(Action),
+    /// Activate the view as if it had been tapped.
+    View(ViewId),
+    /// Send the key event to the view even though it isn't focused.
+    Forward(ViewId),
+}
+
+/// Whether an accelerator wins over the focused view or only applies when the focused
+/// view doesn't produce an action for the key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AcceleratorPriority {
+    BeforeFocused,
+    AfterFocused,
+}
+
+#[derive(Debug, Clone)]
+pub struct Accelerator {
+    pub combo: KeyCombo,
+    pub target: AcceleratorTarget,
+    pub priority: AcceleratorPriority,
+}
+
+#[derive(Debug)]
+pub struct AcceleratorScope {
+    pub owner: ViewId,
+    /// A modal scope hides the accelerators of every scope below it.
+    pub modal: bool,
+    pub accelerators: Vec<Accelerator>,
+}
+
+impl AcceleratorScope {
+    pub(crate) fn new(owner: ViewId, modal: bool) -> AcceleratorScope {
+        AcceleratorScope {
+            owner,
+            modal,
+            accelerators: vec![],
+        }
+    }
+}
+
+impl<C> Scene<C> {
+    /// Bind a key combo in the topmost scope. Replaces an existing binding for the same combo.
+    pub fn add_accelerator(
+        &mut self,
+        combo: KeyCombo,
+        target: AcceleratorTarget,
+        priority: AcceleratorPriority,
+    ) {
+        if let Some(scope) = self.accelerators.last_mut() {
+            scope.accelerators.retain(|acc| acc.combo != combo);
+            scope.accelerators.push(Accelerator {
+                combo,
+                target,
+                priority,
+            });
+        }
+    }
+    pub fn remove_accelerator(&mut self, combo: &KeyCombo) {
+        if let Some(scope) = self.accelerators.last_mut() {
+            scope.accelerators.retain(|acc| &acc.combo != combo);
+        }
+    }
+    /// Start a new scope of accelerators, for example while a dialog is open.
+    pub fn push_accelerator_scope(&mut self, owner: &ViewId, modal: bool) {
+        self.accelerators.push(AcceleratorScope::new(*owner, modal));
+    }
+    /// Remove the topmost scope and all of its accelerators. The root scope is never removed.
+    pub fn pop_accelerator_scope(&mut self) -> Option<AcceleratorScope> {
+        if self.accelerators.len() <= 1 {
+            warn!("can't pop the root accelerator scope");
+            return None;
+        }
+        self.accelerators.pop()
+    }
+    /// Find the binding for a key, searching from the topmost scope down.
+    /// Returns the owner of the scope it was found in along with the target.
+    pub fn find_accelerator(
+        &self,
+        key: &KeyEvent,
+        priority: AcceleratorPriority,
+    ) -> Option<(ViewId, AcceleratorTarget)> {
+        for scope in self.accelerators.iter().rev() {
+            if let Some(acc) = scope
+                .accelerators
+                .iter()
+                .find(|acc| acc.priority == priority && acc.combo.matches(key))
+            {
+                return Some((scope.owner, acc.target.clone()));
+            }
+            if scope.modal {
+                break;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::accelerator::{AcceleratorPriority, AcceleratorTarget, KeyCombo};
+    use crate::button::make_button;
+    use crate::scene::{Scene, event_at_focused};
+    use crate::text_input::make_text_input;
+    use crate::view::ViewId;
+    use crate::{Action, EventType, Key, KeyEvent, Modifiers};
+
+    fn key(key: KeyEvent) -> EventType {
+        EventType::Key(key)
+    }
+
+    #[test]
+    fn test_accelerators() {
+        let mut scene = Scene::new();
+        let root = scene.root_id();
+        let save = ViewId::new("save");
+        scene.add_view_to_root(make_button(&save, "Save"));
+        scene.add_accelerator(
+            KeyCombo::ctrl(Key::Char('s')),
+            AcceleratorTarget::View(save),
+            AcceleratorPriority::BeforeFocused,
+        );
+        scene.add_accelerator(
+            KeyCombo::new(Key::Escape),
+            AcceleratorTarget::Action(Action::Back),
+            AcceleratorPriority::AfterFocused,
+        );
+
+        // works with nothing focused
+        let ctrl_s = KeyEvent::text('s').with_modifiers(Modifiers::ctrl());
+        let res = event_at_focused(&mut scene, &key(ctrl_s.clone()), &mut ());
+        assert!(matches!(res, Some((id, Action::Generic)) if id == save));
+        // modifiers must match exactly
+        assert!(event_at_focused(&mut scene, &key(KeyEvent::text('s')), &mut ()).is_none());
+
+        // before-focused accelerators win over a focused text input
+        let input = make_text_input("input", "abc");
+        let input_id = input.name;
+        scene.add_view_to_root(input);
+        scene.set_focused(&input_id);
+        let res = event_at_focused(&mut scene, &key(ctrl_s.clone()), &mut ());
+        assert!(matches!(res, Some((id, Action::Generic)) if id == save));
+        // after-focused ones only apply when the focused view doesn't produce an action
+        let res = event_at_focused(&mut scene, &key(KeyEvent::pressed(Key::Escape)), &mut ());
+        assert!(matches!(res, Some((id, Action::Back)) if id == root));
+
+        // a modal scope hides the root accelerators until it is popped
+        let dialog = ViewId::new("dialog");
+        scene.push_accelerator_scope(&dialog, true);
+        scene.add_accelerator(
+            KeyCombo::new(Key::Escape),
+            AcceleratorTarget::Action(Action::Command("close".into())),
+            AcceleratorPriority::BeforeFocused,
+        );
+        let res = event_at_focused(&mut scene, &key(KeyEvent::pressed(Key::Escape)), &mut ());
+        assert!(matches!(res, Some((id, Action::Command(cmd))) if id == dialog && cmd == "close"));
+        assert!(event_at_focused(&mut scene, &key(ctrl_s.clone()), &mut ()).is_none());
+        assert!(scene.pop_accelerator_scope().is_some());
+        assert!(event_at_focused(&mut scene, &key(ctrl_s), &mut ()).is_some());
+        assert!(scene.pop_accelerator_scope().is_none());
+    }
+}
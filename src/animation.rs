@@ -0,0 +1,286 @@
+use crate::geom::Bounds;
+use crate::scene::Scene;
+use crate::view::{View, ViewId};
+use alloc::vec::Vec;
+use embedded_graphics::pixelcolor::Rgb565;
+use embedded_graphics::prelude::RgbColor;
+
+/// Animation progress is fixed point, from 0 to `ONE`, so it works without an FPU.
+pub const ONE: i32 = 1024;
+/// How often the app should call `tick` while an animation is running.
+pub const FRAME_MS: u64 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnimationId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Map linear progress `t` in 0..=ONE onto the curve.
+    pub fn apply(&self, t: i32) -> i32 {
+        let t = t.clamp(0, ONE);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t / ONE,
+            Easing::EaseOut => ONE - (ONE - t) * (ONE - t) / ONE,
+            Easing::EaseInOut => {
+                if t < ONE / 2 {
+                    2 * t * t / ONE
+                } else {
+                    ONE - 2 * (ONE - t) * (ONE - t) / ONE
+                }
+            }
+        }
+    }
+}
+
+/// What an animation changes. Colors and values live in view state, so the view provides
+/// a setter that stores the interpolated value.
+#[derive(Debug)]
+pub enum AnimationTarget<C = ()> {
+    Bounds(Bounds, Bounds),
+    Color(Rgb565, Rgb565, fn(&mut View<C>, Rgb565)),
+    Value(i32, i32, fn(&mut View<C>, i32)),
+}
+
+fn lerp(from: i32, to: i32, t: i32) -> i32 {
+    from + (to - from) * t / ONE
+}
+
+fn lerp_color(from: Rgb565, to: Rgb565, t: i32) -> Rgb565 {
+    Rgb565::new(
+        lerp(from.r() as i32, to.r() as i32, t) as u8,
+        lerp(from.g() as i32, to.g() as i32, t) as u8,
+        lerp(from.b() as i32, to.b() as i32, t) as u8,
+    )
+}
+
+impl<C> Clone for AnimationTarget<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for AnimationTarget<C> {}
+
+impl<C> AnimationTarget<C> {
+    fn apply(&self, view: &mut View<C>, t: i32) {
+        match self {
+            AnimationTarget::Bounds(from, to) => {
+                view.bounds = Bounds::new(
+                    lerp(from.x(), to.x(), t),
+                    lerp(from.y(), to.y(), t),
+                    lerp(from.w(), to.w(), t),
+                    lerp(from.h(), to.h(), t),
+                );
+            }
+            AnimationTarget::Color(from, to, set) => set(view, lerp_color(*from, *to, t)),
+            AnimationTarget::Value(from, to, set) => set(view, lerp(*from, *to, t)),
+        }
+    }
+    /// True if both change the same property, which for colors and values means the same
+    /// setter.
+    fn same_property(&self, other: &AnimationTarget<C>) -> bool {
+        match (self, other) {
+            (AnimationTarget::Bounds(..), AnimationTarget::Bounds(..)) => true,
+            (AnimationTarget::Color(_, _, a), AnimationTarget::Color(_, _, b)) => {
+                core::ptr::fn_addr_eq(*a, *b)
+            }
+            (AnimationTarget::Value(_, _, a), AnimationTarget::Value(_, _, b)) => {
+                core::ptr::fn_addr_eq(*a, *b)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Animation<C> {
+    id: AnimationId,
+    view: ViewId,
+    target: AnimationTarget<C>,
+    easing: Easing,
+    start_ms: u64,
+    duration_ms: u64,
+}
+
+impl<C> Animation<C> {
+    pub(crate) fn is_for(&self, view: &ViewId) -> bool {
+        &self.view == view
+    }
+}
+
+impl<C> Scene<C> {
+    /// Animate a property of the view, starting at the time of the last `tick`. The view
+    /// receives `EventType::AnimationDone` when it finishes. An animation already running on
+    /// the same property is cancelled, so the new one takes over from wherever it got to.
+    pub fn animate(
+        &mut self,
+        view: &ViewId,
+        target: AnimationTarget<C>,
+        duration_ms: u64,
+        easing: Easing,
+    ) -> AnimationId {
+        self.next_animation_id += 1;
+        let id = AnimationId(self.next_animation_id);
+        self.animations
+            .retain(|anim| !(anim.is_for(view) && anim.target.same_property(&target)));
+        self.animations.push(Animation {
+            id,
+            view: *view,
+            target,
+            easing,
+            start_ms: self.now_ms,
+            duration_ms,
+        });
+        id
+    }
+    /// Stop an animation where it is, without a completion event.
+    pub fn cancel_animation(&mut self, id: AnimationId) {
+        self.animations.retain(|anim| anim.id != id);
+    }
+    pub fn is_animating(&self, view: &ViewId) -> bool {
+        self.animations.iter().any(|anim| anim.is_for(view))
+    }
+    /// Apply every running animation at `now_ms`. Returns the ones that finished.
+    pub(crate) fn step_animations(&mut self, now_ms: u64) -> Vec<(AnimationId, ViewId)> {
+        let mut finished = Vec::new();
+        let animations = core::mem::take(&mut self.animations);
+        for anim in animations {
+            let elapsed = now_ms.saturating_sub(anim.start_ms);
+            let t = if elapsed >= anim.duration_ms {
+                ONE
+            } else {
+                (elapsed * ONE as u64 / anim.duration_ms) as i32
+            };
+            // dirty the old and the new area
+            self.mark_dirty_view(&anim.view);
+            let Some(view) = self.get_view_mut(&anim.view) else {
+                continue;
+            };
+            anim.target.apply(view, anim.easing.apply(t));
+            self.mark_dirty_view(&anim.view);
+            if t == ONE {
+                finished.push((anim.id, anim.view));
+            } else {
+                self.animations.push(anim);
+            }
+        }
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::animation::{AnimationTarget, Easing, ONE};
+    use crate::geom::Bounds;
+    use crate::scene::Scene;
+    use crate::view::{View, ViewId};
+    use crate::{Action, EventType, Handler};
+
+    #[test]
+    fn test_easing() {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            assert_eq!(easing.apply(0), 0);
+            assert_eq!(easing.apply(ONE), ONE);
+        }
+        assert_eq!(Easing::Linear.apply(ONE / 4), 256);
+        assert_eq!(Easing::EaseIn.apply(ONE / 2), 256);
+        assert_eq!(Easing::EaseOut.apply(ONE / 2), 768);
+        assert_eq!(Easing::EaseInOut.apply(ONE / 2), 512);
+        assert_eq!(Easing::EaseInOut.apply(ONE / 4), 128);
+    }
+
+    #[test]
+    fn test_animate_bounds() {
+        let mut scene = Scene::new();
+        let panel: ViewId = "panel".into();
+        scene.add_view_to_root(View {
+            name: panel,
+            bounds: Bounds::new(0, 10, 20, 20),
+            input: Some(Handler::Fn(|e| match e.event_type {
+                EventType::AnimationDone(_) => Some(Action::Command("done".into())),
+                _ => None,
+            })),
+            ..Default::default()
+        });
+        scene.tick(1000, &mut ());
+        scene.animate(
+            &panel,
+            AnimationTarget::Bounds(Bounds::new(0, 10, 20, 20), Bounds::new(100, 10, 20, 20)),
+            100,
+            Easing::Linear,
+        );
+        assert_eq!(scene.next_deadline(), Some(1020));
+
+        scene.dirty_rect = Bounds::new_empty();
+        assert!(scene.tick(1050, &mut ()).is_empty());
+        assert_eq!(
+            scene.get_view_bounds(&panel),
+            Some(Bounds::new(50, 10, 20, 20))
+        );
+        // only the old and new positions are dirty
+        assert_eq!(scene.dirty_rect, Bounds::new(0, 10, 70, 20));
+
+        let results = scene.tick(1100, &mut ());
+        assert_eq!(
+            scene.get_view_bounds(&panel),
+            Some(Bounds::new(100, 10, 20, 20))
+        );
+        assert!(matches!(&results[0], (id, Action::Command(cmd)) if id == &panel && cmd == "done"));
+        assert!(!scene.is_animating(&panel));
+        assert_eq!(scene.next_deadline(), None);
+    }
+
+    #[test]
+    fn test_replace_animation() {
+        let mut scene = Scene::new();
+        let panel: ViewId = "panel".into();
+        scene.add_view_to_root(View {
+            name: panel,
+            bounds: Bounds::new(0, 10, 20, 20),
+            input: Some(Handler::Fn(|e| match e.event_type {
+                EventType::AnimationDone(_) => Some(Action::Generic),
+                _ => None,
+            })),
+            ..Default::default()
+        });
+        scene.tick(1000, &mut ());
+        scene.animate(
+            &panel,
+            AnimationTarget::Bounds(Bounds::new(0, 10, 20, 20), Bounds::new(100, 10, 20, 20)),
+            100,
+            Easing::Linear,
+        );
+        scene.tick(1050, &mut ());
+        // heading back halfway through replaces the first animation
+        scene.animate(
+            &panel,
+            AnimationTarget::Bounds(Bounds::new(50, 10, 20, 20), Bounds::new(0, 10, 20, 20)),
+            100,
+            Easing::Linear,
+        );
+        assert_eq!(scene.animations.len(), 1);
+        assert!(scene.tick(1100, &mut ()).is_empty());
+        assert_eq!(
+            scene.get_view_bounds(&panel),
+            Some(Bounds::new(25, 10, 20, 20))
+        );
+        assert_eq!(scene.tick(1150, &mut ()).len(), 1);
+        assert_eq!(
+            scene.get_view_bounds(&panel),
+            Some(Bounds::new(0, 10, 20, 20))
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use crate::gfx::draw_centered_text;
 use crate::view::Flex::Intrinsic;
 use crate::view::{View, ViewId};
-use crate::{Action, DrawEvent, EventType, util};
+use crate::{Action, DrawEvent, EventType, Handler, util};
 use alloc::string::ToString;
 
-fn draw_button(e: &mut DrawEvent) {
-    e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
+fn draw_button<C>(e: &mut DrawEvent<C>) {
+    let bg = *e.button_bg();
+    e.ctx.fill_rect(&e.view.bounds, &bg);
     e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
     if let Some(focused) = e.focused {
         if focused == &e.view.name {
@@ -21,25 +22,24 @@ fn draw_button(e: &mut DrawEvent) {
     );
 }
 
-pub fn make_button(name: &ViewId, title: &str) -> View {
+pub fn make_button<C>(name: &ViewId, title: &str) -> View<C> {
     View {
         name: name.clone(),
         title: title.to_string(),
         h_flex: Intrinsic,
         v_flex: Intrinsic,
-        input: Some(|e| {
+        input: Some(Handler::Fn(|e| {
             if let EventType::Tap(_pt) = &e.event_type {
                 e.scene.set_focused(e.target);
                 return Some(Action::Generic);
             }
             None
-        }),
-        layout: Some(|e| {
-            if let Some(view) = e.scene.get_view_mut(&e.target) {
-                view.bounds.size = util::calc_size(e.theme.bold_font, &view.title);
-            }
-        }),
-        draw: Some(draw_button),
+        })),
+        measure: Some(Handler::Fn(|e| match e.scene.get_view(e.target) {
+            Some(view) => util::calc_size(e.theme.bold_font, &view.title),
+            None => e.space,
+        })),
+        draw: Some(Handler::Fn(draw_button)),
         ..Default::default()
     }
 }
@@ -1,12 +1,11 @@
 use crate::geom::{Bounds, Point as GPoint};
 use crate::gfx::{DrawingContext, TextStyle};
-use crate::view::Align;
+use crate::view::{Align, Direction};
 use core::ops::Add;
 use embedded_graphics::Drawable;
 use embedded_graphics::draw_target::DrawTargetExt;
 use embedded_graphics::geometry::{Point as EPoint, Size as ESize};
 use embedded_graphics::mono_font::MonoTextStyleBuilder;
-use embedded_graphics::mono_font::ascii::FONT_6X10;
 use embedded_graphics::pixelcolor::Rgb565;
 use embedded_graphics::prelude::DrawTarget;
 use embedded_graphics::primitives::{Line, Primitive, PrimitiveStyle, Rectangle};
@@ -19,6 +18,7 @@ where
     pub display: &'a mut T,
     pub clip: Bounds,
     offset: EPoint,
+    direction: Direction,
 }
 
 impl<'a, T> EmbeddedDrawingContext<'a, T>
@@ -30,6 +30,7 @@ where
             display,
             clip: Bounds::new_empty(),
             offset: EPoint::new(0, 0),
+            direction: Direction::Ltr,
         }
     }
 }
@@ -79,18 +80,20 @@ where
         let style = text_builder.build(); // MonoTextStyle::new(&FONT_6X10,  *text_style.color);
         let mut pt = EPoint::new(bounds.position.x, bounds.position.y);
         pt.y += bounds.size.h / 2;
-        pt.y += (FONT_6X10.baseline as i32) / 2;
+        pt.y += (text_style.font.baseline as i32) / 2;
 
-        let w = (FONT_6X10.character_size.width as i32) * (text.len() as i32);
+        let w = (text_style.font.character_size.width as i32) * (text.chars().count() as i32);
 
-        match text_style.halign {
+        match text_style.halign.for_direction(self.direction) {
             Align::Start => {
                 pt.x += 5;
             }
             Align::Center => {
                 pt.x += (bounds.size.w - w) / 2;
             }
-            Align::End => {}
+            Align::End => {
+                pt.x += bounds.size.w - w - 5;
+            }
         }
 
         Text::new(text, pt, style).draw(&mut display);
@@ -120,4 +123,17 @@ where
     fn translate(&mut self, offset: &GPoint) {
         self.offset = self.offset.add(EPoint::new(offset.x, offset.y));
     }
+    fn clip_to(&mut self, bounds: &Bounds) -> Bounds {
+        let old = self.clip;
+        self.clip = self
+            .clip
+            .intersection(*bounds + GPoint::new(self.offset.x, self.offset.y));
+        old
+    }
+    fn restore_clip(&mut self, clip: Bounds) {
+        self.clip = clip;
+    }
+    fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
 }
@@ -0,0 +1,203 @@
+use crate::geom::{Bounds, Point, Size};
+use crate::view::Align::{Center, End, Start};
+use crate::view::Flex::Resize;
+use crate::view::{Align, View, ViewId};
+use crate::{Handler, LayoutEvent, MeasureEvent};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Settings for a flow panel, stored as its view state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlowLayoutState {
+    /// Space between children on a line.
+    pub h_gap: i32,
+    /// Space between lines.
+    pub v_gap: i32,
+    /// Where each line sits when it doesn't fill the width.
+    pub line_align: Align,
+}
+
+impl Default for FlowLayoutState {
+    fn default() -> Self {
+        FlowLayoutState {
+            h_gap: 0,
+            v_gap: 0,
+            line_align: Start,
+        }
+    }
+}
+
+pub fn make_flow_panel<C>(name: &ViewId) -> View<C> {
+    View {
+        name: *name,
+        title: name.as_str().into(),
+        h_flex: Resize,
+        state: Some(Box::new(FlowLayoutState::default())),
+        layout: Some(Handler::Fn(layout_flow)),
+        measure: Some(Handler::Fn(measure_flow)),
+        ..Default::default()
+    }
+}
+
+struct Line {
+    kids: Vec<(ViewId, Size)>,
+    width: i32,
+    height: i32,
+}
+
+/// Break the children into lines no wider than `width`, by their outer sizes.
+fn break_lines(
+    kids: Vec<ViewId>,
+    width: i32,
+    h_gap: i32,
+    measure: &mut dyn FnMut(&ViewId) -> Size,
+) -> Vec<Line> {
+    let mut lines: Vec<Line> = vec![];
+    for kid in kids {
+        let size = measure(&kid);
+        match lines.last_mut() {
+            Some(line) if line.width + h_gap + size.w <= width => {
+                line.width += h_gap + size.w;
+                line.height = line.height.max(size.h);
+                line.kids.push((kid, size));
+            }
+            _ => lines.push(Line {
+                kids: vec![(kid, size)],
+                width: size.w,
+                height: size.h,
+            }),
+        }
+    }
+    lines
+}
+
+/// The size of the panel for its lines.
+fn flow_size<C>(view: &View<C>, space: Size, lines: &[Line], v_gap: i32) -> Size {
+    let content = Size::new(
+        lines.iter().map(|line| line.width).max().unwrap_or(0),
+        lines.iter().map(|line| line.height).sum::<i32>() + v_gap * (lines.len() as i32 - 1).max(0),
+    );
+    view.resolve_size(space, content)
+}
+
+/// Places the children left to right at their own size, starting a new line when the
+/// next child doesn't fit. Children are aligned within their line by `v_align`. An
+/// intrinsic height panel grows to fit all of its lines.
+pub fn layout_flow<C>(pass: &mut LayoutEvent<C>) {
+    let Some(view) = pass.scene.get_view(pass.target) else {
+        return;
+    };
+    let padding = view.padding;
+    let state = view
+        .get_state_ref::<FlowLayoutState>()
+        .copied()
+        .unwrap_or_default();
+    let inner = pass.space - padding;
+    let kids = pass.scene.get_children_ids(pass.target);
+    let lines = break_lines(kids, inner.w, state.h_gap, &mut |kid| {
+        pass.measure_child(kid, inner)
+    });
+
+    // position them
+    let mut y = padding.top;
+    for line in &lines {
+        let mut x = padding.left
+            + match state.line_align {
+                Start => 0,
+                Center => (inner.w - line.width) / 2,
+                End => inner.w - line.width,
+            };
+        for (kid, size) in &line.kids {
+            if let Some(view) = pass.scene.get_view(kid) {
+                let dy = match view.v_align {
+                    Start => 0,
+                    Center => (line.height - size.h) / 2,
+                    End => line.height - size.h,
+                };
+                let position = Point::new(x + view.margin.left, y + dy + view.margin.top);
+                let bounds = Bounds::new_from(position, *size - view.margin);
+                pass.arrange_child(kid, bounds);
+            }
+            x += size.w + state.h_gap;
+        }
+        y += line.height + state.v_gap;
+    }
+
+    let size = pass
+        .scene
+        .get_view(pass.target)
+        .map(|view| flow_size(view, pass.space, &lines, state.v_gap));
+    if let Some(view) = pass.scene.get_view_mut(pass.target)
+        && let Some(size) = size
+    {
+        view.bounds.size = size;
+    }
+}
+
+/// The measuring half of `layout_flow`, so the height can be found for a given width.
+pub fn measure_flow<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    let state = view
+        .get_state_ref::<FlowLayoutState>()
+        .copied()
+        .unwrap_or_default();
+    let inner = e.space - view.padding;
+    let kids = e.scene.get_children_ids(e.target);
+    let lines = break_lines(kids, inner.w, state.h_gap, &mut |kid| {
+        e.measure_child(kid, inner)
+    });
+    flow_size(view, e.space, &lines, state.v_gap)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::flow::{FlowLayoutState, make_flow_panel};
+    use crate::geom::{Bounds, Insets, Size};
+    use crate::scene::{Scene, layout_scene};
+    use crate::test::MockDrawingContext;
+    use crate::view::Align::{Center, End};
+    use crate::view::{View, ViewId};
+    use alloc::boxed::Box;
+
+    #[test]
+    fn test_flow_wraps() {
+        let theme = MockDrawingContext::make_mock_theme();
+        let mut scene: Scene = Scene::new();
+        let flow_id = ViewId::new("flow");
+        let mut flow = make_flow_panel(&flow_id);
+        flow.padding = Insets::new_same(5);
+        flow.state = Some(Box::new(FlowLayoutState {
+            h_gap: 4,
+            v_gap: 2,
+            line_align: Center,
+        }));
+        // three chips fit in the 190px wide line, the fourth wraps
+        let chips = [("a", 60, 20), ("b", 60, 10), ("c", 60, 20), ("d", 40, 20)];
+        for (name, w, h) in chips {
+            scene.add_view_to_parent(
+                View {
+                    name: name.into(),
+                    v_align: End,
+                    layout: None,
+                    ..Default::default()
+                }
+                .with_size(w, h),
+                &flow_id,
+            );
+        }
+        scene.add_view_to_root(flow);
+        layout_scene(&mut scene, &theme);
+
+        let bounds = |name: &'static str| scene.get_view(&ViewId::new(name)).unwrap().bounds;
+        // the first line is 188 wide, centered in 190
+        assert_eq!(bounds("a"), Bounds::new(6, 5, 60, 20));
+        assert_eq!(bounds("b"), Bounds::new(70, 15, 60, 10));
+        assert_eq!(bounds("c"), Bounds::new(134, 5, 60, 20));
+        assert_eq!(bounds("d"), Bounds::new(80, 27, 40, 20));
+        // the panel is as tall as its two lines
+        assert_eq!(bounds("flow").size, Size::new(200, 52));
+    }
+}
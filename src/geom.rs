@@ -289,6 +289,17 @@ impl Bounds {
             self.y2().max(b.y2()),
         )
     }
+    /// The area covered by both, or an empty bounds if they don't overlap.
+    pub fn intersection(&self, b: Bounds) -> Bounds {
+        let x = self.position.x.max(b.position.x);
+        let y = self.position.y.max(b.position.y);
+        let x2 = self.x2().min(b.x2());
+        let y2 = self.y2().min(b.y2());
+        if x2 <= x || y2 <= y {
+            return Bounds::new_empty();
+        }
+        Bounds::from_xyxy2(x, y, x2, y2)
+    }
     pub(crate) fn center(&self) -> Point {
         Point::new(
             self.position.x + self.size.w / 2,
@@ -384,6 +395,11 @@ mod tests {
         let b3 = Bounds::new(140, 180, 80, 30);
         // INFO - union Bounds { x: 140, y: 180, w: 80, h: 30 } Bounds { x: 140, y: 180, w: 80, h: 30 }
         assert_eq!(b2.union(b3), b2.clone());
+        assert_eq!(
+            bounds.intersection(Bounds::new(50, 80, 100, 100)),
+            Bounds::new(50, 80, 50, 20)
+        );
+        assert!(bounds.intersection(b2).is_empty());
     }
     #[test]
     fn test_point() {
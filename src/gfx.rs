@@ -1,5 +1,5 @@
 use crate::geom::{Bounds, Point};
-use crate::view::Align;
+use crate::view::{Align, Direction};
 use embedded_graphics::mono_font::MonoFont;
 use embedded_graphics::pixelcolor::Rgb565;
 
@@ -48,6 +48,12 @@ pub trait DrawingContext {
     fn fill_text(&mut self, bounds: &Bounds, text: &str, style: &TextStyle);
     fn text(&mut self, text: &str, position: &Point, style: &TextStyle);
     fn translate(&mut self, offset: &Point);
+    /// Limit drawing to the part of the current clip inside `bounds`, which is in translated
+    /// coordinates. Returns the previous clip to hand back to `restore_clip`.
+    fn clip_to(&mut self, bounds: &Bounds) -> Bounds;
+    fn restore_clip(&mut self, clip: Bounds);
+    /// The direction of the view being drawn. Text follows it when aligned to `Start` or `End`.
+    fn set_direction(&mut self, direction: Direction);
 }
 
 pub fn draw_centered_text(
@@ -1,18 +1,60 @@
-use crate::geom::{Bounds, Point};
-use crate::view::Flex::{Intrinsic, Resize};
+use crate::geom::{Bounds, Point, Size};
+use crate::layouts::{distribute_space, mirror_children};
+use crate::view::Flex::Resize;
 use crate::view::{Align, View, ViewId};
-use crate::{DrawEvent, LayoutEvent};
+use crate::{DrawEvent, Handler, LayoutEvent, MeasureEvent};
 use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
 use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
-use hashbrown::HashMap;
+use hashbrown::{HashMap, HashSet};
+use log::warn;
+
+/// How a grid row or column is sized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Track {
+    /// A fixed number of pixels.
+    Fixed(i32),
+    /// Fit the largest child that sits only in this track.
+    Auto,
+    /// A weighted share of the space left after the fixed and auto tracks. Grids that
+    /// size to their content give fractional tracks no space.
+    Fraction(u32),
+}
+
+/// The order children without a constraint are placed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridFlow {
+    /// Fill each row left to right, adding rows as needed.
+    Row,
+    /// Fill each column top to bottom, adding columns as needed.
+    Column,
+}
+
+/// A problem with the constraints of a grid, found by [`GridLayoutState::validate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridError {
+    /// The child's cell or span goes past the last row or column.
+    OutOfRange(ViewId),
+    /// A span of zero rows or columns.
+    EmptySpan(ViewId),
+    /// Two children share a cell.
+    Overlap(ViewId, ViewId),
+}
 
 pub struct GridLayoutState {
     pub constraints: HashMap<ViewId, LayoutConstraint>,
-    row_count: usize,
-    col_count: usize,
-    col_width: usize,
-    row_height: usize,
+    pub rows: Vec<Track>,
+    pub columns: Vec<Track>,
+    pub row_gap: i32,
+    pub col_gap: i32,
+    pub flow: GridFlow,
+    /// The size of the rows or columns added by auto-placement.
+    pub implicit_track: Track,
     pub debug: bool,
+    /// The track sizes from the last layout pass.
+    row_sizes: Vec<i32>,
+    col_sizes: Vec<i32>,
 }
 
 impl GridLayoutState {
@@ -22,15 +64,34 @@ impl GridLayoutState {
         col_count: usize,
         col_width: usize,
     ) -> GridLayoutState {
+        GridLayoutState::new_tracks(
+            vec![Track::Fixed(row_height as i32); row_count],
+            vec![Track::Fixed(col_width as i32); col_count],
+        )
+    }
+    pub fn new_tracks(rows: Vec<Track>, columns: Vec<Track>) -> GridLayoutState {
         GridLayoutState {
             constraints: HashMap::new(),
-            col_count,
-            row_count,
-            col_width,
-            row_height,
+            rows,
+            columns,
+            row_gap: 0,
+            col_gap: 0,
+            flow: GridFlow::Row,
+            implicit_track: Track::Auto,
             debug: false,
+            row_sizes: vec![],
+            col_sizes: vec![],
         }
     }
+    pub fn with_gaps(mut self, row_gap: i32, col_gap: i32) -> GridLayoutState {
+        self.row_gap = row_gap;
+        self.col_gap = col_gap;
+        self
+    }
+    pub fn with_flow(mut self, flow: GridFlow) -> GridLayoutState {
+        self.flow = flow;
+        self
+    }
 }
 
 impl GridLayoutState {
@@ -43,8 +104,170 @@ impl GridLayoutState {
         self.constraints
             .insert(name.clone(), LayoutConstraint::at_row_column(row, col))
     }
+    /// Check the constraints against the tracks and each other.
+    pub fn validate(&self) -> Result<(), Vec<GridError>> {
+        let mut errors = vec![];
+        let mut owners: HashMap<(usize, usize), ViewId> = HashMap::new();
+        // sort so overlaps are reported the same way every time, by name for the same cell
+        let mut constraints: Vec<_> = self.constraints.iter().collect();
+        constraints.sort_by_key(|(name, cons)| (cons.row, cons.col, name.as_str()));
+        for (name, cons) in constraints {
+            if cons.row_span == 0 || cons.col_span == 0 {
+                errors.push(GridError::EmptySpan(*name));
+                continue;
+            }
+            if cons.row + cons.row_span > self.rows.len()
+                || cons.col + cons.col_span > self.columns.len()
+            {
+                errors.push(GridError::OutOfRange(*name));
+            }
+            let mut overlaps = vec![];
+            for cell in cons.cells() {
+                match owners.get(&cell) {
+                    Some(other) if !overlaps.contains(other) => overlaps.push(*other),
+                    Some(_) => {}
+                    None => {
+                        owners.insert(cell, *name);
+                    }
+                }
+            }
+            errors.extend(
+                overlaps
+                    .into_iter()
+                    .map(|other| GridError::Overlap(other, *name)),
+            );
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+    /// Find a cell for every child. Children without a constraint flow into the next free
+    /// cells, in the order they were added. Also returns the number of implicit tracks.
+    fn place_children(&self, kids: &[ViewId]) -> (Vec<(ViewId, LayoutConstraint)>, usize) {
+        let taken: HashSet<(usize, usize)> = self
+            .constraints
+            .values()
+            .flat_map(|cons| cons.cells())
+            .collect();
+        let (rows, cols) = (self.rows.len().max(1), self.columns.len().max(1));
+        let cell_at = |i: usize| match self.flow {
+            GridFlow::Row => (i / cols, i % cols),
+            GridFlow::Column => (i % rows, i / rows),
+        };
+        let mut cursor = 0;
+        let mut placed = vec![];
+        for kid in kids {
+            if let Some(cons) = self.constraints.get(kid) {
+                placed.push((*kid, *cons));
+                continue;
+            }
+            while taken.contains(&cell_at(cursor)) {
+                cursor += 1;
+            }
+            let (row, col) = cell_at(cursor);
+            placed.push((*kid, LayoutConstraint::at_row_column(row, col)));
+            cursor += 1;
+        }
+        let extra = match self.flow {
+            GridFlow::Row => cursor.div_ceil(cols).saturating_sub(self.rows.len()),
+            GridFlow::Column => cursor.div_ceil(rows).saturating_sub(self.columns.len()),
+        };
+        (placed, extra)
+    }
+    /// The cell of every child, and the rows and columns including the implicit ones.
+    fn plan(&self, kids: &[ViewId]) -> (Vec<(ViewId, LayoutConstraint)>, Vec<Track>, Vec<Track>) {
+        let (placed, extra) = self.place_children(kids);
+        let mut rows = self.rows.clone();
+        let mut columns = self.columns.clone();
+        match self.flow {
+            GridFlow::Row => rows.extend(vec![self.implicit_track; extra]),
+            GridFlow::Column => columns.extend(vec![self.implicit_track; extra]),
+        }
+        (placed, rows, columns)
+    }
+    /// The bounds of a cell, relative to the grid's padded box. Spans include the gaps
+    /// between their tracks.
+    fn cell_bounds(&self, cons: &LayoutConstraint) -> Bounds {
+        let (x, w) = span_extent(&self.col_sizes, self.col_gap, cons.col, cons.col_span);
+        let (y, h) = span_extent(&self.row_sizes, self.row_gap, cons.row, cons.row_span);
+        Bounds::new(x, y, w, h)
+    }
 }
 
+/// The start and length of `span` tracks starting at `start`. Missing tracks are empty.
+fn span_extent(sizes: &[i32], gap: i32, start: usize, span: usize) -> (i32, i32) {
+    let size = |i: usize| sizes.get(i).copied().unwrap_or(0);
+    let pos = (0..start).map(|i| size(i) + gap).sum();
+    let len = (start..start + span).map(size).sum::<i32>() + gap * (span.max(1) as i32 - 1);
+    (pos, len)
+}
+
+/// The content size of a track, by track index.
+type TrackContent = Vec<(usize, i32)>;
+
+/// The outer width and height of each child that sits in a single column or row, by track
+/// index, for sizing the auto tracks.
+fn measure_cells(
+    placed: &[(ViewId, LayoutConstraint)],
+    space: Size,
+    measure: &mut dyn FnMut(&ViewId, Size) -> Size,
+) -> (TrackContent, TrackContent) {
+    let mut col_content = vec![];
+    let mut row_content = vec![];
+    for (kid, cons) in placed {
+        let size = measure(kid, space);
+        if cons.col_span == 1 {
+            col_content.push((cons.col, size.w));
+        }
+        if cons.row_span == 1 {
+            row_content.push((cons.row, size.h));
+        }
+    }
+    (col_content, row_content)
+}
+
+/// Resolve the tracks of one axis. `content` holds the size of each child that sits in a
+/// single track, by track index. `avail` is the space to fill, or `None` to fit content.
+fn size_tracks(
+    tracks: &[Track],
+    gap: i32,
+    content: &[(usize, i32)],
+    avail: Option<i32>,
+) -> Vec<i32> {
+    let mut sizes: Vec<i32> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, track)| match track {
+            Track::Fixed(px) => *px,
+            Track::Auto => content
+                .iter()
+                .filter(|(track, _)| *track == i)
+                .map(|(_, size)| *size)
+                .max()
+                .unwrap_or(0),
+            Track::Fraction(_) => 0,
+        })
+        .collect();
+    if let Some(avail) = avail {
+        let gaps = gap * (tracks.len() as i32 - 1).max(0);
+        let leftover = (avail - sizes.iter().sum::<i32>() - gaps).max(0);
+        let weights: Vec<u32> = tracks
+            .iter()
+            .map(|track| match track {
+                Track::Fraction(weight) => *weight,
+                _ => 0,
+            })
+            .collect();
+        for (size, share) in sizes.iter_mut().zip(distribute_space(leftover, &weights)) {
+            *size += share;
+        }
+    }
+    sizes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct LayoutConstraint {
     pub col: usize,
     pub row: usize,
@@ -61,94 +284,138 @@ impl LayoutConstraint {
             row_span: 1,
         }
     }
+    fn cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.row..self.row + self.row_span)
+            .flat_map(move |row| (self.col..self.col + self.col_span).map(move |col| (row, col)))
+    }
 }
 
-pub fn make_grid_panel(name: &ViewId) -> View {
+pub fn make_grid_panel<C>(name: &ViewId) -> View<C> {
     View {
         name: name.clone(),
         title: name.as_str().into(),
-        state: Some(Box::new(GridLayoutState {
-            constraints: HashMap::new(),
-            col_count: 2,
-            row_count: 2,
-            col_width: 100,
-            row_height: 30,
-            debug: false,
-        })),
-        layout: Some(layout_grid),
-        draw: Some(draw_grid),
+        state: Some(Box::new(GridLayoutState::new_row_column(2, 30, 2, 100))),
+        layout: Some(Handler::Fn(layout_grid)),
+        measure: Some(Handler::Fn(measure_grid)),
+        draw: Some(Handler::Fn(draw_grid)),
         visible: true,
         ..Default::default()
     }
 }
 
-fn draw_grid(evt: &mut DrawEvent) {
+fn draw_grid<C>(evt: &mut DrawEvent<C>) {
     let bounds = evt.view.bounds;
     evt.ctx.fill_rect(&evt.view.bounds, &evt.theme.bg);
     evt.ctx.stroke_rect(&evt.view.bounds, &evt.theme.fg);
     let padding = evt.view.padding;
     if let Some(state) = evt.view.get_state::<GridLayoutState>() {
         if state.debug {
-            for i in 0..state.col_count + 1 {
-                let x = (i * state.col_width) as i32 + bounds.x() + padding.left;
-                let y = bounds.y() + padding.top;
-                let y2 = bounds.y() + bounds.h() - padding.top * 2;
-                evt.ctx
-                    .line(&Point::new(x, y), &Point::new(x, y2), &Rgb565::RED);
-            }
-            for j in 0..state.row_count + 1 {
-                let y = (j * state.row_height) as i32 + bounds.y() + padding.top;
-                let x = bounds.x() + padding.left;
-                let x2 = bounds.x() + bounds.w() - padding.left * 2;
-                evt.ctx
-                    .line(&Point::new(x, y), &Point::new(x2, y), &Rgb565::RED);
+            let inner = bounds - padding;
+            // outline every cell so gaps show up too
+            for row in 0..state.row_sizes.len() {
+                for col in 0..state.col_sizes.len() {
+                    let cell = state.cell_bounds(&LayoutConstraint::at_row_column(row, col));
+                    evt.ctx.stroke_rect(&(cell + inner.position), &Rgb565::RED);
+                }
             }
         }
     }
 }
 
-fn layout_grid(pass: &mut LayoutEvent) {
+fn layout_grid<C>(pass: &mut LayoutEvent<C>) {
+    let Some(view) = pass.scene.get_view_mut(pass.target) else {
+        return;
+    };
+    let h_flex = view.h_flex;
+    let v_flex = view.v_flex;
+    let padding = view.padding;
+    view.bounds.size = view.resolve_size(pass.space, view.bounds.size - padding);
+    let space = view.bounds.size - padding;
+    let kids = pass.scene.get_children_ids(pass.target);
+    let Some(state) = pass.scene.get_view_state::<GridLayoutState>(pass.target) else {
+        return;
+    };
+    if state.debug
+        && let Err(errors) = state.validate()
+    {
+        warn!("grid {} has bad constraints {:?}", pass.target, errors);
+    }
+    let (placed, rows, columns) = state.plan(&kids);
+
+    // measure the children for the auto tracks
+    let (col_content, row_content) = measure_cells(&placed, space, &mut |kid, space| {
+        pass.measure_child(kid, space)
+    });
+
+    let Some(state) = pass.scene.get_view_state::<GridLayoutState>(pass.target) else {
+        return;
+    };
+    let avail_w = if h_flex == Resize {
+        Some(space.w)
+    } else {
+        None
+    };
+    let avail_h = if v_flex == Resize {
+        Some(space.h)
+    } else {
+        None
+    };
+    state.col_sizes = size_tracks(&columns, state.col_gap, &col_content, avail_w);
+    state.row_sizes = size_tracks(&rows, state.row_gap, &row_content, avail_h);
+    let (_, total_w) = span_extent(&state.col_sizes, state.col_gap, 0, columns.len());
+    let (_, total_h) = span_extent(&state.row_sizes, state.row_gap, 0, rows.len());
+    let cells: Vec<(ViewId, Bounds)> = placed
+        .iter()
+        .map(|(kid, cons)| {
+            let cell = state.cell_bounds(cons) + Point::new(padding.left, padding.top);
+            (*kid, cell)
+        })
+        .collect();
+
     if let Some(view) = pass.scene.get_view_mut(pass.target) {
-        if view.h_flex == Resize {
-            view.bounds.size.w = pass.space.w;
-        }
-        if view.h_flex == Intrinsic {}
-        if view.v_flex == Resize {
-            view.bounds.size.h = pass.space.h;
-        }
-        if view.v_flex == Intrinsic {}
+        view.bounds.size = view.resolve_size(pass.space, Size::new(total_w, total_h));
+    }
 
-        let parent_bounds = view.bounds.clone();
-        let padding = view.padding.clone();
-        let kids = pass.scene.get_children_ids(pass.target);
-        let space = parent_bounds.size.clone() - padding;
-        for kid in kids {
-            pass.layout_child(&kid, space);
-            if let Some(state) = pass.scene.get_view_state::<GridLayoutState>(pass.target) {
-                let cell_bounds = if let Some(cons) = &state.constraints.get(&kid) {
-                    let x = (cons.col * state.col_width) as i32 + padding.left;
-                    let y = (cons.row * state.row_height) as i32 + padding.top;
-                    let w = state.col_width as i32 * cons.col_span as i32;
-                    let h = state.row_height as i32 * cons.row_span as i32;
-                    Bounds::new(x, y, w, h)
-                } else {
-                    Bounds::new(0, 0, 0, 0)
-                };
-                if let Some(view) = pass.scene.get_view_mut(&kid) {
-                    view.bounds.position.x = match &view.h_align {
-                        Align::Start => cell_bounds.x(),
-                        Align::Center => cell_bounds.x() + (cell_bounds.w() - view.bounds.w()) / 2,
-                        Align::End => cell_bounds.x() + cell_bounds.w() - view.bounds.w(),
-                    };
-                    view.bounds.position.y = match &view.v_align {
-                        Align::Start => cell_bounds.y(),
-                        Align::Center => cell_bounds.y() + (cell_bounds.h() - view.bounds.h()) / 2,
-                        Align::End => cell_bounds.y() + cell_bounds.h() - view.bounds.h(),
-                    };
-                }
-            }
+    // fit each child to its cell
+    for (kid, cell_bounds) in cells {
+        pass.layout_child(&kid, cell_bounds.size);
+        if let Some(view) = pass.scene.get_view_mut(&kid) {
+            let cell_bounds = cell_bounds - view.margin;
+            view.bounds.position.x = match &view.h_align {
+                Align::Start => cell_bounds.x(),
+                Align::Center => cell_bounds.x() + (cell_bounds.w() - view.bounds.w()) / 2,
+                Align::End => cell_bounds.x() + cell_bounds.w() - view.bounds.w(),
+            };
+            view.bounds.position.y = match &view.v_align {
+                Align::Start => cell_bounds.y(),
+                Align::Center => cell_bounds.y() + (cell_bounds.h() - view.bounds.h()) / 2,
+                Align::End => cell_bounds.y() + cell_bounds.h() - view.bounds.h(),
+            };
         }
     }
+    mirror_children(pass.scene, pass.target);
+}
+
+/// The measuring half of `layout_grid`.
+pub fn measure_grid<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    let Some(state) = view.get_state_ref::<GridLayoutState>() else {
+        return view.bounds.size;
+    };
+    let padding = view.padding;
+    let size = view.resolve_size(e.space, view.bounds.size - padding);
+    let kids = e.scene.get_children_ids(e.target);
+    let (placed, rows, columns) = state.plan(&kids);
+    let (col_content, row_content) = measure_cells(&placed, size - padding, &mut |kid, space| {
+        e.measure_child(kid, space)
+    });
+    let col_sizes = size_tracks(&columns, state.col_gap, &col_content, None);
+    let row_sizes = size_tracks(&rows, state.row_gap, &row_content, None);
+    let (_, total_w) = span_extent(&col_sizes, state.col_gap, 0, columns.len());
+    let (_, total_h) = span_extent(&row_sizes, state.row_gap, 0, rows.len());
+    view.resolve_size(e.space, Size::new(total_w, total_h))
 }
 
 impl Into<ViewId> for &'static str {
@@ -157,22 +424,30 @@ impl Into<ViewId> for &'static str {
     }
 }
 
+#[cfg(test)]
 mod tests {
+    use crate::Handler;
     use crate::button::make_button;
-    use crate::geom::Bounds;
-    use crate::grid::{GridLayoutState, LayoutConstraint, make_grid_panel};
+    use crate::geom::{Bounds, Point, Size};
+    use crate::grid::{
+        GridError, GridFlow, GridLayoutState, LayoutConstraint, Track, make_grid_panel,
+    };
     use crate::label::make_label;
+    use crate::layouts::layout_std_panel;
     use crate::scene::{Scene, draw_scene, layout_scene};
     use crate::test::MockDrawingContext;
     use crate::view::Align::Start;
+    use crate::view::Flex::{Intrinsic, Resize};
+    use crate::view::View;
     use crate::view::ViewId;
     use alloc::boxed::Box;
+    use alloc::vec;
 
     #[test]
     fn test_grid_layout() {
         let theme = MockDrawingContext::make_mock_theme();
 
-        let mut grid = make_grid_panel(&ViewId::new("grid"));
+        let mut grid: View = make_grid_panel(&ViewId::new("grid"));
         grid.bounds = Bounds::new(40, 40, 200, 200);
         let mut grid_layout = GridLayoutState::new_row_column(2, 30, 2, 100);
 
@@ -225,7 +500,7 @@ mod tests {
     #[test]
     fn col_span() {
         let theme = MockDrawingContext::make_mock_theme();
-        let mut grid = make_grid_panel(&ViewId::new("grid"))
+        let mut grid: View = make_grid_panel(&ViewId::new("grid"))
             .position_at(0, 0)
             .with_size(200, 200);
         let mut layout = GridLayoutState::new_row_column(2, 30, 2, 100);
@@ -252,4 +527,153 @@ mod tests {
             assert_eq!(view.bounds, Bounds::new(86, 2, 28, 25));
         }
     }
+
+    #[test]
+    fn test_grid_tracks() {
+        let theme = MockDrawingContext::make_mock_theme();
+        let mut scene = Scene::new();
+        let grid_id = ViewId::new("grid");
+        let mut grid = make_grid_panel(&grid_id);
+        grid.h_flex = Resize;
+        grid.v_flex = Resize;
+        let mut layout = GridLayoutState::new_tracks(
+            vec![Track::Auto, Track::Fixed(20)],
+            vec![
+                Track::Fixed(30),
+                Track::Auto,
+                Track::Fraction(1),
+                Track::Fraction(2),
+            ],
+        )
+        .with_gaps(4, 5);
+
+        let label = View {
+            name: "label".into(),
+            layout: None,
+            ..Default::default()
+        }
+        .with_size(50, 12);
+        layout.place_at_row_column(&label.name, 0, 1);
+        scene.add_view_to_parent(label, &grid_id);
+        let field = View {
+            name: "field".into(),
+            h_flex: Resize,
+            v_flex: Resize,
+            layout: Some(Handler::Fn(layout_std_panel)),
+            ..Default::default()
+        };
+        layout.constraints.insert(
+            field.name,
+            LayoutConstraint {
+                col: 2,
+                row: 1,
+                col_span: 2,
+                row_span: 1,
+            },
+        );
+        scene.add_view_to_parent(field, &grid_id);
+        grid.state = Some(Box::new(layout));
+        scene.add_view_to_root(grid);
+        layout_scene(&mut scene, &theme);
+
+        // columns are 30, 50, 35 and 70 wide and rows 12 and 20 high
+        let bounds =
+            |scene: &Scene, name: &'static str| scene.get_view(&ViewId::new(name)).unwrap().bounds;
+        assert_eq!(bounds(&scene, "label"), Bounds::new(35, 0, 50, 12));
+        // the span covers the gap between its columns
+        assert_eq!(bounds(&scene, "field"), Bounds::new(90, 16, 110, 20));
+
+        // a grid sized to its content leaves the fractional columns empty
+        if let Some(view) = scene.get_view_mut(&grid_id) {
+            view.h_flex = Intrinsic;
+            view.v_flex = Intrinsic;
+        }
+        scene.mark_layout_dirty();
+        layout_scene(&mut scene, &theme);
+        assert_eq!(bounds(&scene, "grid").size, Size::new(95, 36));
+    }
+
+    #[test]
+    fn test_auto_placement() {
+        let theme = MockDrawingContext::make_mock_theme();
+        let cell = |name: &'static str| -> View {
+            View {
+                name: name.into(),
+                layout: None,
+                h_align: Start,
+                v_align: Start,
+                ..Default::default()
+            }
+            .with_size(20, 10)
+        };
+        // b is placed by hand, the others flow around it into implicit tracks that fit them
+        for (flow, expected, size) in [
+            (
+                GridFlow::Row,
+                [(0, 0), (0, 30), (100, 30)],
+                Size::new(200, 40),
+            ),
+            (
+                GridFlow::Column,
+                [(0, 0), (200, 0), (220, 0)],
+                Size::new(240, 30),
+            ),
+        ] {
+            let mut scene = Scene::new();
+            let grid_id = ViewId::new("grid");
+            let mut grid = make_grid_panel(&grid_id);
+            let mut layout = GridLayoutState::new_tracks(
+                vec![Track::Fixed(30)],
+                vec![Track::Fixed(100), Track::Fixed(100)],
+            )
+            .with_flow(flow);
+            layout.place_at_row_column(&"b".into(), 0, 1);
+            for name in ["a", "b", "c", "d"] {
+                scene.add_view_to_parent(cell(name), &grid_id);
+            }
+            grid.state = Some(Box::new(layout));
+            scene.add_view_to_root(grid);
+            layout_scene(&mut scene, &theme);
+            for (name, (x, y)) in ["a", "c", "d"].iter().zip(expected) {
+                let bounds = scene.get_view(&(*name).into()).unwrap().bounds;
+                assert_eq!(bounds.position, Point::new(x, y), "{flow:?} {name}");
+            }
+            assert_eq!(scene.get_view(&grid_id).unwrap().bounds.size, size);
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut layout = GridLayoutState::new_row_column(2, 30, 2, 100);
+        layout.place_at_row_column(&"a".into(), 0, 0);
+        assert_eq!(layout.validate(), Ok(()));
+        layout.constraints.insert(
+            "wide".into(),
+            LayoutConstraint {
+                col: 0,
+                row: 0,
+                col_span: 2,
+                row_span: 1,
+            },
+        );
+        layout.place_at_row_column(&"lost".into(), 1, 2);
+        layout.constraints.insert(
+            "empty".into(),
+            LayoutConstraint {
+                col: 1,
+                row: 1,
+                col_span: 0,
+                row_span: 1,
+            },
+        );
+        // a and wide start in the same cell, so a owns it by name
+        assert_eq!(
+            layout.validate(),
+            Err(vec![
+                GridError::Overlap("a".into(), "wide".into()),
+                GridError::EmptySpan("empty".into()),
+                GridError::OutOfRange("lost".into()),
+            ])
+        );
+    }
 }
@@ -0,0 +1,767 @@
+use crate::geom::Point;
+use crate::scene::{EventResult, Scene, click_at, drag_at, event_at_focused, hover_at, press_at};
+use crate::{Callback, EventType, Key, KeyEvent, KeyState};
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// A device independent input event, produced by an [`InputSource`] and routed to the scene
+/// with [`dispatch_input`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// A tap or click at a point in scene coordinates, sent when the pointer is released.
+    Tap(Point),
+    /// The pointer went down. The view under it is shown as pressed until the `Tap`.
+    Press(Point),
+    /// The mouse moved. While a button is held this drags like `Drag`.
+    Hover(Point),
+    /// The pointer moved while down, which scrolls the scroll view under the press.
+    Drag(Point),
+    Scroll(i32, i32),
+    Key(KeyEvent),
+}
+
+/// Something that turns native input (a touch controller, a keyboard, the simulator window)
+/// into iris events. `poll` is called from the app's loop with the current time and returns
+/// one pending event at a time, or `None` once there is nothing left to report.
+pub trait InputSource {
+    fn poll(&mut self, now_ms: u64) -> Option<InputEvent>;
+}
+
+/// Route a single event to the scene. Taps go to the view under the point, everything else
+/// goes to the focused view.
+pub fn dispatch_input<C>(
+    scene: &mut Scene<C>,
+    handlers: &Vec<Callback<C>>,
+    event: InputEvent,
+    context: &mut C,
+) -> Option<EventResult> {
+    match event {
+        InputEvent::Tap(pt) => click_at(scene, handlers, pt, context),
+        InputEvent::Press(pt) => {
+            press_at(scene, pt);
+            None
+        }
+        InputEvent::Hover(pt) => {
+            hover_at(scene, pt);
+            None
+        }
+        InputEvent::Drag(pt) => {
+            drag_at(scene, pt);
+            None
+        }
+        InputEvent::Scroll(x, y) => event_at_focused(scene, &EventType::Scroll(x, y), context),
+        InputEvent::Key(key) => event_at_focused(scene, &EventType::Key(key), context),
+    }
+}
+
+/// Drain every pending event from the source into the scene and collect the resulting actions.
+pub fn dispatch_all<C>(
+    scene: &mut Scene<C>,
+    handlers: &Vec<Callback<C>>,
+    source: &mut dyn InputSource,
+    now_ms: u64,
+    context: &mut C,
+) -> Vec<EventResult> {
+    let mut results = vec![];
+    while let Some(event) = source.poll(now_ms) {
+        if let Some(result) = dispatch_input(scene, handlers, event, context) {
+            results.push(result);
+        }
+    }
+    results
+}
+
+fn key_text(key: Key) -> Option<char> {
+    match key {
+        Key::Char(ch) => Some(ch),
+        _ => None,
+    }
+}
+
+fn key_event(key: Key, state: KeyState, repeat: bool) -> InputEvent {
+    InputEvent::Key(KeyEvent {
+        text: if state == KeyState::Pressed {
+            key_text(key)
+        } else {
+            None
+        },
+        state,
+        repeat,
+        ..KeyEvent::pressed(key)
+    })
+}
+
+/// Reads raw samples from a touch panel. Returns `None` while nothing is touching the panel.
+pub trait TouchController {
+    fn read_touch(&mut self) -> Option<(i32, i32)>;
+}
+
+impl<F: FnMut() -> Option<(i32, i32)>> TouchController for F {
+    fn read_touch(&mut self) -> Option<(i32, i32)> {
+        self()
+    }
+}
+
+/// Maps raw touch controller coordinates onto the screen. The raw range is inclusive.
+/// Axes are swapped before inverting, which covers panels mounted sideways.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchCalibration {
+    pub raw_min: Point,
+    pub raw_max: Point,
+    pub width: i32,
+    pub height: i32,
+    pub swap_xy: bool,
+    pub invert_x: bool,
+    pub invert_y: bool,
+}
+
+impl TouchCalibration {
+    /// A panel whose raw coordinates already match the screen.
+    pub fn identity(width: i32, height: i32) -> TouchCalibration {
+        TouchCalibration {
+            raw_min: Point::zero(),
+            raw_max: Point::new(width - 1, height - 1),
+            width,
+            height,
+            swap_xy: false,
+            invert_x: false,
+            invert_y: false,
+        }
+    }
+    pub fn apply(&self, raw: (i32, i32)) -> Point {
+        let (x, y) = if self.swap_xy { (raw.1, raw.0) } else { raw };
+        let mut x = scale(x, self.raw_min.x, self.raw_max.x, self.width);
+        let mut y = scale(y, self.raw_min.y, self.raw_max.y, self.height);
+        if self.invert_x {
+            x = self.width - 1 - x;
+        }
+        if self.invert_y {
+            y = self.height - 1 - y;
+        }
+        Point::new(x, y)
+    }
+}
+
+fn scale(value: i32, min: i32, max: i32, size: i32) -> i32 {
+    if max <= min {
+        return 0;
+    }
+    let value = value.clamp(min, max) - min;
+    value * (size - 1) / (max - min)
+}
+
+/// Turns touch samples into taps. The tap is reported where the finger was last seen when it
+/// lifts, so a press that slides off a button doesn't trigger whatever it started on.
+pub struct TouchInput<T: TouchController> {
+    pub controller: T,
+    pub calibration: TouchCalibration,
+    last: Option<Point>,
+}
+
+impl<T: TouchController> TouchInput<T> {
+    pub fn new(controller: T, calibration: TouchCalibration) -> Self {
+        TouchInput {
+            controller,
+            calibration,
+            last: None,
+        }
+    }
+}
+
+impl<T: TouchController> InputSource for TouchInput<T> {
+    fn poll(&mut self, _now_ms: u64) -> Option<InputEvent> {
+        match self.controller.read_touch() {
+            Some(raw) => {
+                let pt = self.calibration.apply(raw);
+                match self.last.replace(pt) {
+                    // report the first contact so the view can show that it is pressed
+                    None => Some(InputEvent::Press(pt)),
+                    Some(last) if last != pt => Some(InputEvent::Drag(pt)),
+                    Some(_) => None,
+                }
+            }
+            None => self.last.take().map(InputEvent::Tap),
+        }
+    }
+}
+
+/// Keyboards that report one already decoded byte per key press, such as the T-Deck's I2C
+/// keyboard. Control bytes are mapped to navigation keys.
+pub struct ByteKeyboard<F: FnMut() -> Option<u8>> {
+    read: F,
+}
+
+impl<F: FnMut() -> Option<u8>> ByteKeyboard<F> {
+    pub fn new(read: F) -> Self {
+        ByteKeyboard { read }
+    }
+}
+
+impl<F: FnMut() -> Option<u8>> InputSource for ByteKeyboard<F> {
+    fn poll(&mut self, _now_ms: u64) -> Option<InputEvent> {
+        // NUL means no key on some keyboards, so skip it and keep draining
+        let mut byte = (self.read)()?;
+        while byte == 0 {
+            byte = (self.read)()?;
+        }
+        Some(match byte {
+            8 => key_event(Key::Backspace, KeyState::Pressed, false),
+            9 => key_event(Key::Tab, KeyState::Pressed, false),
+            10 | 13 => key_event(Key::Return, KeyState::Pressed, false),
+            27 => key_event(Key::Escape, KeyState::Pressed, false),
+            127 => key_event(Key::Delete, KeyState::Pressed, false),
+            byte => InputEvent::Key(KeyEvent::text(byte as char)),
+        })
+    }
+}
+
+/// Scans a row/column key matrix. `is_down(row, col)` reads one switch and `keymap` holds
+/// `rows * cols` keys in row-major order. Held keys repeat after `repeat_delay_ms`.
+pub struct MatrixKeyboard<F: FnMut(usize, usize) -> bool> {
+    is_down: F,
+    keymap: &'static [Key],
+    cols: usize,
+    down: Vec<bool>,
+    held_since: Option<(usize, u64)>,
+    pub repeat_delay_ms: u64,
+    pub repeat_interval_ms: u64,
+    pending: VecDeque<InputEvent>,
+}
+
+impl<F: FnMut(usize, usize) -> bool> MatrixKeyboard<F> {
+    pub fn new(is_down: F, keymap: &'static [Key], cols: usize) -> Self {
+        MatrixKeyboard {
+            is_down,
+            keymap,
+            cols,
+            down: vec![false; keymap.len()],
+            held_since: None,
+            repeat_delay_ms: 500,
+            repeat_interval_ms: 100,
+            pending: VecDeque::new(),
+        }
+    }
+    fn scan(&mut self, now_ms: u64) {
+        for (i, key) in self.keymap.iter().enumerate() {
+            let down = (self.is_down)(i / self.cols, i % self.cols);
+            if down == self.down[i] {
+                continue;
+            }
+            self.down[i] = down;
+            if down {
+                self.held_since = Some((i, now_ms));
+                self.pending
+                    .push_back(key_event(*key, KeyState::Pressed, false));
+            } else {
+                if self.held_since.is_some_and(|(held, _)| held == i) {
+                    self.held_since = None;
+                }
+                self.pending
+                    .push_back(key_event(*key, KeyState::Released, false));
+            }
+        }
+        // only the most recently pressed key repeats
+        if let Some((i, since)) = self.held_since
+            && now_ms >= since + self.repeat_delay_ms
+        {
+            self.pending
+                .push_back(key_event(self.keymap[i], KeyState::Pressed, true));
+            // a zero interval would repeat on every poll at the same time, forever
+            let interval = self.repeat_interval_ms.max(1);
+            self.held_since = Some((i, now_ms - self.repeat_delay_ms + interval));
+        }
+    }
+}
+
+impl<F: FnMut(usize, usize) -> bool> InputSource for MatrixKeyboard<F> {
+    fn poll(&mut self, now_ms: u64) -> Option<InputEvent> {
+        if self.pending.is_empty() {
+            self.scan(now_ms);
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Turns accumulated movement counts into arrow keys, one key per `threshold` counts, and a
+/// click into Return. `read` returns the motion since the last call and whether the ball is
+/// pressed.
+pub struct Trackball<F: FnMut() -> (i32, i32, bool)> {
+    read: F,
+    pub threshold: i32,
+    dx: i32,
+    dy: i32,
+    was_pressed: bool,
+    pending: VecDeque<InputEvent>,
+}
+
+impl<F: FnMut() -> (i32, i32, bool)> Trackball<F> {
+    pub fn new(read: F, threshold: i32) -> Self {
+        Trackball {
+            read,
+            threshold: threshold.max(1),
+            dx: 0,
+            dy: 0,
+            was_pressed: false,
+            pending: VecDeque::new(),
+        }
+    }
+    fn sample(&mut self) {
+        let (dx, dy, pressed) = (self.read)();
+        self.dx += dx;
+        self.dy += dy;
+        let threshold = self.threshold.max(1);
+        while self.dx.abs() >= threshold {
+            let key = if self.dx > 0 { Key::Right } else { Key::Left };
+            self.dx -= threshold * self.dx.signum();
+            self.pending
+                .push_back(key_event(key, KeyState::Pressed, false));
+        }
+        while self.dy.abs() >= threshold {
+            let key = if self.dy > 0 { Key::Down } else { Key::Up };
+            self.dy -= threshold * self.dy.signum();
+            self.pending
+                .push_back(key_event(key, KeyState::Pressed, false));
+        }
+        if pressed != self.was_pressed {
+            self.was_pressed = pressed;
+            let state = if pressed {
+                KeyState::Pressed
+            } else {
+                KeyState::Released
+            };
+            self.pending.push_back(key_event(Key::Return, state, false));
+        }
+    }
+}
+
+impl<F: FnMut() -> (i32, i32, bool)> InputSource for Trackball<F> {
+    fn poll(&mut self, _now_ms: u64) -> Option<InputEvent> {
+        if self.pending.is_empty() {
+            self.sample();
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// A quadrature rotary encoder with an optional push switch. `read` returns the absolute
+/// count and the switch state. Every `counts_per_detent` counts become one vertical scroll
+/// step, and pushing the knob sends Return.
+pub struct RotaryEncoder<F: FnMut() -> (i32, bool)> {
+    read: F,
+    pub counts_per_detent: i32,
+    last_count: Option<i32>,
+    remainder: i32,
+    was_pressed: bool,
+    pending: VecDeque<InputEvent>,
+}
+
+impl<F: FnMut() -> (i32, bool)> RotaryEncoder<F> {
+    pub fn new(read: F, counts_per_detent: i32) -> Self {
+        RotaryEncoder {
+            read,
+            counts_per_detent: counts_per_detent.max(1),
+            last_count: None,
+            remainder: 0,
+            was_pressed: false,
+            pending: VecDeque::new(),
+        }
+    }
+    fn sample(&mut self) {
+        let (count, pressed) = (self.read)();
+        if let Some(last) = self.last_count {
+            self.remainder += count.wrapping_sub(last);
+            let counts_per_detent = self.counts_per_detent.max(1);
+            let steps = self.remainder / counts_per_detent;
+            if steps != 0 {
+                self.remainder -= steps * counts_per_detent;
+                self.pending.push_back(InputEvent::Scroll(0, steps));
+            }
+        }
+        self.last_count = Some(count);
+        if pressed != self.was_pressed {
+            self.was_pressed = pressed;
+            let state = if pressed {
+                KeyState::Pressed
+            } else {
+                KeyState::Released
+            };
+            self.pending.push_back(key_event(Key::Return, state, false));
+        }
+    }
+}
+
+impl<F: FnMut() -> (i32, bool)> InputSource for RotaryEncoder<F> {
+    fn poll(&mut self, _now_ms: u64) -> Option<InputEvent> {
+        if self.pending.is_empty() {
+            self.sample();
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Plain push buttons wired to GPIO pins. `is_down(i)` reads button `i` and `keys[i]` is the
+/// key it sends. A button has to hold a new level for `debounce_ms` before it is reported.
+pub struct GpioButtons<F: FnMut(usize) -> bool> {
+    is_down: F,
+    keys: &'static [Key],
+    pub debounce_ms: u64,
+    stable: Vec<bool>,
+    changed_at: Vec<Option<u64>>,
+    pending: VecDeque<InputEvent>,
+}
+
+impl<F: FnMut(usize) -> bool> GpioButtons<F> {
+    pub fn new(is_down: F, keys: &'static [Key]) -> Self {
+        GpioButtons {
+            is_down,
+            keys,
+            debounce_ms: 20,
+            stable: vec![false; keys.len()],
+            changed_at: vec![None; keys.len()],
+            pending: VecDeque::new(),
+        }
+    }
+    fn sample(&mut self, now_ms: u64) {
+        for (i, key) in self.keys.iter().enumerate() {
+            let down = (self.is_down)(i);
+            if down == self.stable[i] {
+                self.changed_at[i] = None;
+                continue;
+            }
+            let since = *self.changed_at[i].get_or_insert(now_ms);
+            if now_ms >= since + self.debounce_ms {
+                self.stable[i] = down;
+                self.changed_at[i] = None;
+                let state = if down {
+                    KeyState::Pressed
+                } else {
+                    KeyState::Released
+                };
+                self.pending.push_back(key_event(*key, state, false));
+            }
+        }
+    }
+}
+
+impl<F: FnMut(usize) -> bool> InputSource for GpioButtons<F> {
+    fn poll(&mut self, now_ms: u64) -> Option<InputEvent> {
+        if self.pending.is_empty() {
+            self.sample(now_ms);
+        }
+        self.pending.pop_front()
+    }
+}
+
+/// Adapter for the embedded-graphics simulator window. Wrap `window.events()` each frame.
+/// A quit request is recorded in `quit` instead of being turned into an event.
+#[cfg(feature = "std")]
+pub struct SimulatorInput<I: Iterator<Item = embedded_graphics_simulator::SimulatorEvent>> {
+    events: I,
+    pub quit: bool,
+}
+
+#[cfg(feature = "std")]
+impl<I: Iterator<Item = embedded_graphics_simulator::SimulatorEvent>> SimulatorInput<I> {
+    pub fn new(events: I) -> Self {
+        SimulatorInput {
+            events,
+            quit: false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<I: Iterator<Item = embedded_graphics_simulator::SimulatorEvent>> InputSource
+    for SimulatorInput<I>
+{
+    fn poll(&mut self, _now_ms: u64) -> Option<InputEvent> {
+        use embedded_graphics_simulator::SimulatorEvent;
+        // skip the keys that have no iris equivalent
+        loop {
+            let event = match self.events.next()? {
+                SimulatorEvent::Quit => {
+                    self.quit = true;
+                    return None;
+                }
+                SimulatorEvent::KeyDown {
+                    keycode,
+                    keymod,
+                    repeat,
+                } => sdl::key_event(keycode, keymod, KeyState::Pressed, repeat),
+                SimulatorEvent::KeyUp {
+                    keycode,
+                    keymod,
+                    repeat,
+                } => sdl::key_event(keycode, keymod, KeyState::Released, repeat),
+                SimulatorEvent::MouseButtonDown { point, .. } => {
+                    Some(InputEvent::Press(Point::new(point.x, point.y)))
+                }
+                SimulatorEvent::MouseButtonUp { point, .. } => {
+                    Some(InputEvent::Tap(Point::new(point.x, point.y)))
+                }
+                SimulatorEvent::MouseMove { point } => {
+                    Some(InputEvent::Hover(Point::new(point.x, point.y)))
+                }
+                SimulatorEvent::MouseWheel { scroll_delta, .. } => {
+                    Some(InputEvent::Scroll(scroll_delta.x, scroll_delta.y))
+                }
+            };
+            if event.is_some() {
+                return event;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+mod sdl {
+    use crate::input::InputEvent;
+    use crate::{Key, KeyEvent, KeyState, Modifiers};
+    use embedded_graphics_simulator::sdl2::{Keycode, Mod};
+
+    pub(crate) fn key_event(
+        keycode: Keycode,
+        keymod: Mod,
+        state: KeyState,
+        repeat: bool,
+    ) -> Option<InputEvent> {
+        let modifiers = Modifiers {
+            shift: keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD),
+            ctrl: keymod.intersects(Mod::LCTRLMOD | Mod::RCTRLMOD),
+            alt: keymod.intersects(Mod::LALTMOD | Mod::RALTMOD),
+            meta: keymod.intersects(Mod::LGUIMOD | Mod::RGUIMOD),
+        };
+        let code = keycode.into_i32();
+        let key = match keycode {
+            Keycode::BACKSPACE => Key::Backspace,
+            Keycode::DELETE => Key::Delete,
+            Keycode::RETURN | Keycode::KP_ENTER => Key::Return,
+            Keycode::TAB => Key::Tab,
+            Keycode::ESCAPE => Key::Escape,
+            Keycode::LEFT => Key::Left,
+            Keycode::RIGHT => Key::Right,
+            Keycode::UP => Key::Up,
+            Keycode::DOWN => Key::Down,
+            Keycode::HOME => Key::Home,
+            Keycode::END => Key::End,
+            Keycode::PAGEUP => Key::PageUp,
+            Keycode::PAGEDOWN => Key::PageDown,
+            _ if (Keycode::F1.into_i32()..=Keycode::F12.into_i32()).contains(&code) => {
+                Key::F((code - Keycode::F1.into_i32() + 1) as u8)
+            }
+            _ if (Keycode::F13.into_i32()..=Keycode::F24.into_i32()).contains(&code) => {
+                Key::F((code - Keycode::F13.into_i32() + 13) as u8)
+            }
+            _ => match char::from_u32(code as u32) {
+                Some(ch) if ch == ' ' || ch.is_ascii_graphic() => Key::Char(ch),
+                _ => return None,
+            },
+        };
+        // SDL only reports the unshifted key, so derive the typed text for letters ourselves
+        let text = match key {
+            Key::Char(ch) if state == KeyState::Pressed && modifiers.shift => {
+                Some(ch.to_ascii_uppercase())
+            }
+            Key::Char(ch) if state == KeyState::Pressed => Some(ch),
+            _ => None,
+        };
+        Some(InputEvent::Key(KeyEvent {
+            key,
+            text,
+            modifiers,
+            state,
+            repeat,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::button::make_button;
+    use crate::geom::Point;
+    use crate::input::{
+        ByteKeyboard, GpioButtons, InputEvent, InputSource, MatrixKeyboard, RotaryEncoder,
+        TouchCalibration, TouchInput, Trackball, dispatch_all,
+    };
+    use crate::scene::Scene;
+    use crate::view::ViewId;
+    use crate::{Action, Key, KeyEvent, KeyState};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::cell::Cell;
+
+    fn drain(source: &mut dyn InputSource, now_ms: u64) -> Vec<InputEvent> {
+        let mut events = vec![];
+        while let Some(evt) = source.poll(now_ms) {
+            events.push(evt);
+        }
+        events
+    }
+
+    #[test]
+    fn touch_calibration() {
+        // a 240x320 portrait panel mounted sideways on a 320x240 screen
+        let cal = TouchCalibration {
+            raw_min: Point::new(0, 0),
+            raw_max: Point::new(319, 239),
+            width: 320,
+            height: 240,
+            swap_xy: true,
+            invert_x: true,
+            invert_y: true,
+        };
+        assert_eq!(cal.apply((0, 0)), Point::new(319, 239));
+        assert_eq!(cal.apply((239, 319)), Point::new(0, 0));
+        // samples outside the calibrated range are clamped
+        assert_eq!(cal.apply((-20, 400)), Point::new(0, 239));
+    }
+
+    #[test]
+    fn touch_taps_on_release() {
+        let mut samples = vec![None, Some((10, 10)), Some((12, 14)), None, None].into_iter();
+        let mut touch = TouchInput::new(
+            move || samples.next().flatten(),
+            TouchCalibration::identity(320, 240),
+        );
+        let events: Vec<_> = (0..5).filter_map(|t| touch.poll(t)).collect();
+        assert_eq!(
+            events,
+            vec![
+                InputEvent::Press(Point::new(10, 10)),
+                InputEvent::Drag(Point::new(12, 14)),
+                InputEvent::Tap(Point::new(12, 14))
+            ]
+        );
+    }
+
+    #[test]
+    fn dispatch_touch_to_scene() {
+        let mut scene = Scene::new();
+        let button = ViewId::new("button");
+        scene.add_view_to_root(make_button(&button, "Go").position_at(20, 20));
+        let mut samples = vec![Some((25, 25)), None].into_iter();
+        let mut touch = TouchInput::new(
+            move || samples.next().flatten(),
+            TouchCalibration::identity(200, 200),
+        );
+        // the press shows the button as pressed and the release taps it
+        let results = dispatch_all(&mut scene, &vec![], &mut touch, 0, &mut ());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], (id, Action::Generic) if id == button));
+        assert_eq!(scene.get_pressed(), None);
+    }
+
+    #[test]
+    fn byte_keyboard() {
+        // a NUL is skipped without holding back the bytes after it
+        let mut bytes = vec![b'h', 0, b'I', 8, 13].into_iter();
+        let mut kb = ByteKeyboard::new(move || bytes.next());
+        let events = drain(&mut kb, 0);
+        assert_eq!(
+            events,
+            vec![
+                InputEvent::Key(KeyEvent::text('h')),
+                InputEvent::Key(KeyEvent::text('I')),
+                InputEvent::Key(KeyEvent::pressed(Key::Backspace)),
+                InputEvent::Key(KeyEvent::pressed(Key::Return)),
+            ]
+        );
+    }
+
+    #[test]
+    fn matrix_keyboard_repeat() {
+        static KEYMAP: [Key; 4] = [Key::Char('a'), Key::Char('b'), Key::Up, Key::Down];
+        // the 'b' key at row 0 col 1 is held from 0ms to 800ms
+        let mut now = 0;
+        let mut kb = MatrixKeyboard::new(|row, col| row == 0 && col == 1, &KEYMAP, 2);
+        let down = drain(&mut kb, now);
+        assert_eq!(down.len(), 1);
+        assert!(
+            matches!(&down[0], InputEvent::Key(k) if k.key == Key::Char('b') && k.text == Some('b') && !k.repeat)
+        );
+        now += 400;
+        assert!(drain(&mut kb, now).is_empty());
+        now += 100;
+        let rep = drain(&mut kb, now);
+        assert!(matches!(&rep[0], InputEvent::Key(k) if k.repeat && k.is_pressed()));
+        now += 50;
+        assert!(drain(&mut kb, now).is_empty());
+        now += 50;
+        assert_eq!(drain(&mut kb, now).len(), 1);
+
+        // a zero interval repeats once per poll instead of forever
+        kb.repeat_interval_ms = 0;
+        now += 100;
+        assert_eq!(drain(&mut kb, now).len(), 1);
+
+        let mut kb2 = MatrixKeyboard::new(|_, _| false, &KEYMAP, 2);
+        kb2.down[1] = true;
+        let up = drain(&mut kb2, 0);
+        assert!(
+            matches!(&up[0], InputEvent::Key(k) if k.key == Key::Char('b') && k.state == KeyState::Released)
+        );
+    }
+
+    #[test]
+    fn trackball_and_encoder() {
+        let mut motion = vec![(0, 7, false), (-4, 0, true), (0, 0, false)].into_iter();
+        let mut ball = Trackball::new(move || motion.next().unwrap_or((0, 0, false)), 3);
+        let keys: Vec<_> = (0..4)
+            .flat_map(|t| drain(&mut ball, t))
+            .map(|e| match e {
+                InputEvent::Key(k) => (k.key, k.state),
+                _ => panic!("unexpected event"),
+            })
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                (Key::Down, KeyState::Pressed),
+                (Key::Down, KeyState::Pressed),
+                (Key::Left, KeyState::Pressed),
+                (Key::Return, KeyState::Pressed),
+                (Key::Return, KeyState::Released),
+            ]
+        );
+
+        // a zero threshold is treated as one count per key
+        let mut motion = vec![(2, 0, false)].into_iter();
+        let mut ball = Trackball::new(move || motion.next().unwrap_or((0, 0, false)), 3);
+        ball.threshold = 0;
+        assert_eq!(drain(&mut ball, 0).len(), 2);
+
+        let mut counts = vec![(100, false), (104, false), (98, false), (97, false)].into_iter();
+        let mut enc = RotaryEncoder::new(move || counts.next().unwrap_or((97, false)), 4);
+        let events: Vec<_> = (0..4).flat_map(|t| drain(&mut enc, t)).collect();
+        assert_eq!(
+            events,
+            vec![InputEvent::Scroll(0, 1), InputEvent::Scroll(0, -1)]
+        );
+
+        // and so is zero counts per detent
+        let mut counts = vec![(0, false), (2, false)].into_iter();
+        let mut enc = RotaryEncoder::new(move || counts.next().unwrap_or((2, false)), 4);
+        enc.counts_per_detent = 0;
+        let events: Vec<_> = (0..2).flat_map(|t| drain(&mut enc, t)).collect();
+        assert_eq!(events, vec![InputEvent::Scroll(0, 2)]);
+    }
+
+    #[test]
+    fn gpio_debounce() {
+        static KEYS: [Key; 1] = [Key::Return];
+        // bounces at 0ms and 5ms, then settles down from 10ms
+        let levels = [true, false, true, true, true, true];
+        let t = Cell::new(0);
+        let mut buttons = GpioButtons::new(|_| levels[(t.get() / 5).min(5)], &KEYS);
+        let mut events = vec![];
+        for now in (0..=30).step_by(5) {
+            t.set(now as usize);
+            events.extend(drain(&mut buttons, now));
+        }
+        assert_eq!(
+            events,
+            vec![InputEvent::Key(KeyEvent::pressed(Key::Return))]
+        );
+    }
+}
@@ -1,23 +1,125 @@
+use crate::geom::{Bounds, Size};
 use crate::gfx::TextStyle;
-use crate::util;
-use crate::view::Flex::Intrinsic;
-use crate::view::{View, ViewId};
+use crate::view::Flex::{Intrinsic, Resize};
+use crate::view::{Align, View, ViewId};
+use crate::{DrawEvent, Handler, MeasureEvent, util};
+use alloc::vec;
+use alloc::vec::Vec;
+use embedded_graphics::mono_font::MonoFont;
 
-pub fn make_label(name: &'static str, title: &str) -> View {
+pub fn make_label<C>(name: &'static str, title: &str) -> View<C> {
     View {
         name: ViewId::new(name),
         title: title.into(),
         h_flex: Intrinsic,
         v_flex: Intrinsic,
-        layout: Some(|e| {
-            if let Some(view) = e.scene.get_view_mut(e.target) {
-                view.bounds.size = util::calc_size(e.theme.font, &view.title);
-            }
-        }),
-        draw: Some(|e| {
+        measure: Some(Handler::Fn(|e| match e.scene.get_view(e.target) {
+            Some(view) => util::calc_size(e.theme.font, &view.title),
+            None => e.space,
+        })),
+        draw: Some(Handler::Fn(|e| {
             let style = TextStyle::new(&e.theme.font, &e.theme.fg);
             e.ctx.fill_text(&e.view.bounds, &e.view.title, &style);
-        }),
+        })),
         ..Default::default()
     }
 }
+
+/// A label that wraps its text to the width it is given, and is as tall as its lines.
+pub fn make_text_block<C>(name: &'static str, text: &str) -> View<C> {
+    View {
+        name: ViewId::new(name),
+        title: text.into(),
+        h_flex: Resize,
+        v_flex: Intrinsic,
+        measure: Some(Handler::Fn(measure_text_block)),
+        draw: Some(Handler::Fn(draw_text_block)),
+        ..Default::default()
+    }
+}
+
+/// Break text into lines of at most `max_chars` characters, at spaces where possible and
+/// at every newline. Words longer than a line are split.
+pub fn wrap_text(text: &str, max_chars: usize) -> Vec<&str> {
+    let max_chars = max_chars.max(1);
+    let mut lines = vec![];
+    for paragraph in text.split('\n') {
+        let mut rest = paragraph.trim_end();
+        if rest.is_empty() {
+            lines.push(rest);
+        }
+        while !rest.is_empty() {
+            let Some((end, _)) = rest.char_indices().nth(max_chars) else {
+                lines.push(rest);
+                break;
+            };
+            let cut = if rest[end..].starts_with(' ') {
+                end
+            } else {
+                rest[..end].rfind(' ').filter(|i| *i > 0).unwrap_or(end)
+            };
+            lines.push(rest[..cut].trim_end());
+            rest = rest[cut..].trim_start();
+        }
+    }
+    lines
+}
+
+/// The characters that fit on a line of a text block `width` pixels wide.
+fn chars_per_line(font: &MonoFont, width: i32) -> usize {
+    let char_w = font.character_size.width as i32;
+    ((width - char_w * 2) / char_w).max(1) as usize
+}
+
+fn measure_text_block<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    let font = &e.theme.font;
+    let lines = wrap_text(&view.title, chars_per_line(font, e.space.w)).len() as i32;
+    let line_h = font.character_size.height as i32;
+    Size::new(e.space.w, line_h * lines + line_h / 2 * 2)
+}
+
+fn draw_text_block<C>(e: &mut DrawEvent<C>) {
+    let bounds = e.view.bounds;
+    let font = &e.theme.font;
+    let line_h = font.character_size.height as i32;
+    let style = TextStyle::new(font, &e.theme.fg).with_halign(Align::Start);
+    let lines = wrap_text(&e.view.title, chars_per_line(font, bounds.w()));
+    for (i, line) in lines.iter().enumerate() {
+        let line_bounds = Bounds::new(
+            bounds.x(),
+            bounds.y() + line_h / 2 + line_h * i as i32,
+            bounds.w(),
+            line_h,
+        );
+        e.ctx.fill_text(&line_bounds, line, &style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::label::wrap_text;
+    use alloc::vec;
+
+    #[test]
+    fn test_wrap_text() {
+        let text = "the quick brown fox jumps over the lazy dog";
+        assert_eq!(
+            wrap_text(text, 23),
+            vec!["the quick brown fox", "jumps over the lazy dog"]
+        );
+        assert_eq!(
+            wrap_text(text, 8),
+            vec![
+                "the", "quick", "brown", "fox", "jumps", "over the", "lazy dog"
+            ]
+        );
+        // long words are split and newlines always break
+        assert_eq!(
+            wrap_text("abcdefghij\n\nok", 4),
+            vec!["abcd", "efgh", "ij", "", "ok"]
+        );
+    }
+}
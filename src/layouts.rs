@@ -1,203 +1,377 @@
-use crate::LayoutEvent;
-use crate::geom::{Insets, Size};
+use crate::geom::{Bounds, Insets, Point, Size};
+use crate::scene::Scene;
 use crate::view::Align::{Center, End, Start};
 use crate::view::Flex::Resize;
-use crate::view::{Flex, ViewId};
+use crate::view::{Align, Direction, Flex, View, ViewId};
+use crate::{LayoutEvent, MeasureEvent};
 use Flex::Intrinsic;
+use alloc::vec;
+use alloc::vec::Vec;
 use log::info;
 
-pub fn layout_vbox(pass: &mut LayoutEvent) {
-    let Some(parent) = pass.scene.get_view_mut(&pass.target) else {
-        info!("view not found!");
-        return;
-    };
-    let h_flex = parent.h_flex.clone();
-    let padding = parent.padding.clone();
-    let mut available_space: Size = pass.space - parent.padding;
+/// The direction a linear layout stacks its children in.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
 
-    // get the intrinsic children
-    let fixed_kids = pass
-        .scene
-        .get_children_ids_filtered(&pass.target, |v| v.v_flex == Intrinsic);
-    // lay out the intrinsic children
-    for kid in &fixed_kids {
-        pass.layout_child(kid, available_space);
+impl Axis {
+    fn main(&self, size: &Size) -> i32 {
+        match self {
+            Axis::Horizontal => size.w,
+            Axis::Vertical => size.h,
+        }
     }
-
-    // calculate total used height
-    let kids_sum = fixed_kids.iter().fold(0, |a, id| {
-        return if let Some(view) = pass.scene.get_view_mut(id) {
-            view.bounds.size.h + a
-        } else {
-            a
-        };
-    });
-    let vert_leftover = (pass.space - padding).h - kids_sum;
-
-    // layout the flex children
-    let flex_kids = pass
-        .scene
-        .get_children_ids_filtered(&pass.target, |v| v.v_flex == Flex::Resize);
-    if flex_kids.len() > 0 {
-        let flex_space = Size {
-            w: pass.space.w - padding.left - padding.right,
-            h: vert_leftover / (flex_kids.len() as i32),
-        };
-        for kid in flex_kids {
-            pass.layout_child(&kid, flex_space);
+    fn cross(&self, size: &Size) -> i32 {
+        match self {
+            Axis::Horizontal => size.h,
+            Axis::Vertical => size.w,
         }
     }
-
-    // calculate the max width of any child
-    let mut max_width = 0;
-    for kid in pass.scene.get_children_ids(&pass.target) {
-        if let Some(kid) = pass.scene.get_view_mut(&kid) {
-            max_width = max_width.max(kid.bounds.size.w);
+    fn size(&self, main: i32, cross: i32) -> Size {
+        match self {
+            Axis::Horizontal => Size::new(main, cross),
+            Axis::Vertical => Size::new(cross, main),
         }
     }
-    if h_flex == Intrinsic {
-        available_space.w = max_width;
+    /// The padding before the first child on the main axis and above it on the cross axis.
+    fn leading(&self, padding: &Insets) -> (i32, i32) {
+        match self {
+            Axis::Horizontal => (padding.left, padding.top),
+            Axis::Vertical => (padding.top, padding.left),
+        }
     }
-
-    // position all the children
-    let mut y = padding.top;
-    let all_kids = pass.scene.get_children_ids(&pass.target);
-    let avail_w = available_space.w;
-    for kid in all_kids {
-        if let Some(kid) = pass.scene.get_view_mut(&kid) {
-            kid.bounds.position.x = match &kid.h_align {
-                Start => 0,
-                Center => (avail_w - kid.bounds.size.w) / 2,
-                End => (avail_w - kid.bounds.size.w),
-            } + padding.left;
-            kid.bounds.position.y = y;
-            y += kid.bounds.size.h;
+    fn main_flex<C>(&self, view: &View<C>) -> Flex {
+        match self {
+            Axis::Horizontal => view.h_flex,
+            Axis::Vertical => view.v_flex,
         }
     }
-    // layout self
-    if let Some(view) = pass.scene.get_view_mut(&pass.target) {
-        if view.h_flex == Resize {
-            view.bounds.size.w = pass.space.w
+    fn cross_flex<C>(&self, view: &View<C>) -> Flex {
+        match self {
+            Axis::Horizontal => view.v_flex,
+            Axis::Vertical => view.h_flex,
         }
-        if view.h_flex == Intrinsic {
-            view.bounds.size.w = max_width + padding.left + padding.right
+    }
+    fn cross_align<C>(&self, view: &View<C>) -> Align {
+        match self {
+            Axis::Horizontal => view.v_align,
+            Axis::Vertical => view.h_align,
         }
-        if view.v_flex == Resize {
-            view.bounds.size.h = pass.space.h
+    }
+}
+
+/// Where a linear layout puts the children along the main axis when they don't fill it.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Justify {
+    Start,
+    Center,
+    End,
+    /// Spread the free space evenly between the children.
+    SpaceBetween,
+    /// Spread the free space evenly around each child, so the ends get half a share.
+    SpaceAround,
+}
+
+/// Optional state for a `layout_hbox` or `layout_vbox` container. Views without it use
+/// the default: no gap, start justified and no stretching.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct LinearLayoutState {
+    /// Space between neighboring children.
+    pub gap: i32,
+    pub justify: Justify,
+    /// Size every child to fill the cross axis, ignoring its alignment.
+    pub stretch: bool,
+}
+
+impl Default for LinearLayoutState {
+    fn default() -> Self {
+        LinearLayoutState {
+            gap: 0,
+            justify: Justify::Start,
+            stretch: false,
         }
-        if view.v_flex == Intrinsic {}
     }
 }
 
-pub fn layout_hbox(pass: &mut LayoutEvent) {
-    let Some(parent) = pass.scene.get_view_mut(&pass.target) else {
-        return;
-    };
-    let h_flex = parent.h_flex.clone();
-    let v_flex = parent.v_flex.clone();
-    // layout self
-    if v_flex == Resize {
-        parent.bounds.size.h = pass.space.h
+/// Split `total` by weight. Rounding leftovers go to the last weighted entry so the
+/// shares add up to `total` exactly.
+pub(crate) fn distribute_space(total: i32, weights: &[u32]) -> Vec<i32> {
+    let sum: u32 = weights.iter().sum();
+    let mut shares: Vec<i32> = weights
+        .iter()
+        .map(|w| {
+            if sum == 0 {
+                0
+            } else {
+                (total as i64 * *w as i64 / sum as i64) as i32
+            }
+        })
+        .collect();
+    if let Some(last) = weights.iter().rposition(|w| *w > 0) {
+        let used: i32 = shares.iter().sum();
+        shares[last] += total - used;
     }
-    if h_flex == Resize {
-        parent.bounds.size.w = pass.space.w
+    shares
+}
+
+/// What a linear layout needs to know about a child to size it.
+struct LinearKid {
+    id: ViewId,
+    main_flex: Flex,
+    grow: u32,
+    shrink: u32,
+    /// The smallest outer size shrinking can take it down to.
+    floor: Size,
+}
+
+fn linear_kids<C>(scene: &Scene<C>, name: &ViewId, axis: Axis) -> Vec<LinearKid> {
+    scene
+        .get_children_ids(name)
+        .iter()
+        .filter_map(|id| scene.get_view(id))
+        .map(|view| LinearKid {
+            id: view.name,
+            main_flex: axis.main_flex(view),
+            grow: view.flex_grow,
+            shrink: view.flex_shrink,
+            floor: view.min_size.unwrap_or(Size::new(0, 0)) + view.margin,
+        })
+        .collect()
+}
+
+/// The outer size of each child. Intrinsic children get their measured size, shrunk by
+/// `flex_shrink` if they overflow, and `Resize` children share what is left by `flex_grow`.
+fn size_linear_kids(
+    axis: Axis,
+    gap: i32,
+    inner: Size,
+    kids: &[LinearKid],
+    measure: &mut dyn FnMut(&ViewId, Size) -> Size,
+) -> Vec<Size> {
+    let mut sizes = vec![Size::new(0, 0); kids.len()];
+    let fixed: Vec<usize> = (0..kids.len())
+        .filter(|i| kids[*i].main_flex == Intrinsic)
+        .collect();
+    for i in &fixed {
+        sizes[*i] = measure(&kids[*i].id, inner);
+    }
+    let gaps = gap * (kids.len() as i32 - 1).max(0);
+    let fixed_sum: i32 = fixed.iter().map(|i| axis.main(&sizes[*i])).sum();
+    let mut leftover = axis.main(&inner) - fixed_sum - gaps;
+    if leftover < 0 {
+        let weights: Vec<u32> = fixed.iter().map(|i| kids[*i].shrink).collect();
+        for (i, cut) in fixed.iter().zip(distribute_space(-leftover, &weights)) {
+            let main = (axis.main(&sizes[*i]) - cut).max(axis.main(&kids[*i].floor));
+            sizes[*i] = axis.size(main, axis.cross(&sizes[*i]));
+        }
+        leftover = 0;
     }
 
-    let padding = parent.padding.clone();
-    let mut available_space = pass.space - padding;
+    let flex: Vec<usize> = (0..kids.len())
+        .filter(|i| kids[*i].main_flex == Resize)
+        .collect();
+    let weights: Vec<u32> = flex.iter().map(|i| kids[*i].grow).collect();
+    for (i, main) in flex.iter().zip(distribute_space(leftover, &weights)) {
+        sizes[*i] = measure(&kids[*i].id, axis.size(main, axis.cross(&inner)));
+    }
+    sizes
+}
 
-    // get the fixed children
-    let fixed_kids = pass
-        .scene
-        .get_children_ids_filtered(&pass.target, |v| v.h_flex == Intrinsic);
+/// The size of the container itself, from the space of the children along the main axis
+/// and the biggest child across it.
+fn linear_size<C>(axis: Axis, view: &View<C>, space: Size, used: i32, max_cross: i32) -> Size {
+    view.resolve_size(space, axis.size(used, max_cross))
+}
 
-    // layout the fixed width children
-    for kid in &fixed_kids {
-        pass.layout_child(kid, available_space);
+/// The space before the first child and after each child, for the free main axis space.
+fn justify_offsets(justify: Justify, free: i32, count: usize) -> (i32, Vec<i32>) {
+    let mut after = vec![0; count];
+    if count == 0 {
+        return (0, after);
     }
+    let lead = match justify {
+        Justify::Start => 0,
+        Justify::Center => free / 2,
+        Justify::End => free,
+        Justify::SpaceBetween => {
+            if count > 1 {
+                let gaps = distribute_space(free, &vec![1; count - 1]);
+                after[..count - 1].copy_from_slice(&gaps);
+            }
+            0
+        }
+        Justify::SpaceAround => {
+            let share = free / count as i32;
+            after.iter_mut().for_each(|a| *a = share);
+            share / 2
+        }
+    };
+    (lead, after)
+}
 
-    // calc the total width of the fixed kids
-    let kids_sum: i32 = fixed_kids
-        .iter()
-        .map(|id| pass.scene.get_view(id))
-        .flatten()
-        .fold(0, |a, v| v.bounds.size.w + a);
-    let avail_horizontal_space = (available_space - padding).h - kids_sum;
+/// Stack the children along `axis`. Intrinsic children keep their size, `Resize`
+/// children share the leftover space by `flex_grow`, and each child is aligned on the
+/// cross axis by its alignment or stretched to fill it.
+pub fn layout_linear<C>(pass: &mut LayoutEvent<C>, axis: Axis) {
+    let Some(parent) = pass.scene.get_view(pass.target) else {
+        info!("view not found!");
+        return;
+    };
+    let cross_flex = axis.cross_flex(parent);
+    let main_flex = axis.main_flex(parent);
+    let padding = parent.padding;
+    let state = parent
+        .get_state_ref::<LinearLayoutState>()
+        .copied()
+        .unwrap_or_default();
+    let inner = pass.space - padding;
 
-    // get the flex children
-    let flex_kids = pass
-        .scene
-        .get_children_ids_filtered(&pass.target, |v| v.h_flex == Flex::Resize);
-    // if there are any flex children
-    if flex_kids.len() > 0 {
-        // split the leftover space
-        let flex_space = Size {
-            w: avail_horizontal_space / (flex_kids.len() as i32),
-            h: pass.space.h - padding.top - padding.bottom,
+    // measure the children
+    let kids = linear_kids(pass.scene, pass.target, axis);
+    let mut sizes = size_linear_kids(axis, state.gap, inner, &kids, &mut |id, space| {
+        pass.measure_child(id, space)
+    });
+    let gaps = state.gap * (kids.len() as i32 - 1).max(0);
+    let used = sizes.iter().map(|s| axis.main(s)).sum::<i32>() + gaps;
+    let max_cross = sizes.iter().map(|s| axis.cross(s)).max().unwrap_or(0);
+    let cross_avail = if cross_flex == Intrinsic {
+        max_cross
+    } else {
+        axis.cross(&inner)
+    };
+    let free = if main_flex == Intrinsic {
+        0
+    } else {
+        (axis.main(&inner) - used).max(0)
+    };
+
+    // arrange them
+    let (lead_main, lead_cross) = axis.leading(&padding);
+    let (lead, after) = justify_offsets(state.justify, free, kids.len());
+    let mut main = lead_main + lead;
+    for (i, kid) in kids.iter().enumerate() {
+        let Some(view) = pass.scene.get_view(&kid.id) else {
+            continue;
         };
-        // layout the flex children
-        for kid in &flex_kids {
-            pass.layout_child(kid, flex_space);
+        let margin = view.margin;
+        if state.stretch {
+            sizes[i] = axis.size(axis.main(&sizes[i]), cross_avail);
         }
+        let size = view.clamp_size(sizes[i] - margin);
+        let outer = size + margin;
+        let cross = lead_cross
+            + match axis.cross_align(view) {
+                Start => 0,
+                Center => (cross_avail - axis.cross(&outer)) / 2,
+                End => cross_avail - axis.cross(&outer),
+            };
+        let position = axis.size(main, cross);
+        let position = Point::new(position.w + margin.left, position.h + margin.top);
+        pass.arrange_child(&kid.id, Bounds::new_from(position, size));
+        main += axis.main(&outer) + state.gap + after[i];
     }
 
-    // calculate the max height of any child
-    let mut max_height = 0;
-    for kid in pass.scene.get_children_ids(&pass.target) {
-        if let Some(kid) = pass.scene.get_view_mut(&kid) {
-            max_height = max_height.max(kid.bounds.size.h);
-        }
+    let size = pass
+        .scene
+        .get_view(pass.target)
+        .map(|parent| linear_size(axis, parent, pass.space, used, max_cross));
+    if let Some(parent) = pass.scene.get_view_mut(pass.target)
+        && let Some(size) = size
+    {
+        parent.bounds.size = size;
     }
+    mirror_children(pass.scene, pass.target);
+}
 
-    // now position all children
-    if v_flex == Intrinsic {
-        available_space.h = max_height;
-    }
-    let avail_h = available_space.h;
-    let mut x = padding.left;
-    for kid in pass.scene.get_children_ids(&pass.target) {
-        if let Some(kid) = pass.scene.get_view_mut(&kid) {
-            kid.bounds.position.x = x;
-            x += kid.bounds.size.w;
-            kid.bounds.position.y = match &kid.v_align {
-                Start => 0,
-                Center => (avail_h - kid.bounds.size.h) / 2,
-                End => (avail_h - kid.bounds.size.h),
-            } + padding.top;
-        }
+/// Flip the children of a right to left view across its padded box, once they are laid
+/// out left to right. This swaps `Start` and `End` horizontally and reverses rows.
+pub(crate) fn mirror_children<C>(scene: &mut Scene<C>, parent: &ViewId) {
+    if scene.get_direction(parent) != Direction::Rtl {
+        return;
     }
-    if let Some(parent) = pass.scene.get_view_mut(pass.target) {
-        if parent.v_flex == Intrinsic {
-            parent.bounds.size.h = available_space.h + padding.top + padding.bottom;
-        }
-        if parent.h_flex == Intrinsic {
-            parent.bounds.size.w = x;
+    let Some(view) = scene.get_view(parent) else {
+        return;
+    };
+    let edges = view.bounds.w() + view.padding.left - view.padding.right;
+    for kid in scene.get_children_ids(parent) {
+        if let Some(view) = scene.get_view_mut(&kid) {
+            view.bounds.position.x = edges - view.bounds.x() - view.bounds.w();
         }
     }
 }
 
-pub fn layout_std_panel(pass: &mut LayoutEvent) {
+/// The measuring half of `layout_linear`.
+pub fn measure_linear<C>(e: &mut MeasureEvent<C>, axis: Axis) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    let state = view
+        .get_state_ref::<LinearLayoutState>()
+        .copied()
+        .unwrap_or_default();
+    let inner = e.space - view.padding;
+    let kids = linear_kids(e.scene, e.target, axis);
+    let sizes = size_linear_kids(axis, state.gap, inner, &kids, &mut |id, space| {
+        e.measure_child(id, space)
+    });
+    let gaps = state.gap * (kids.len() as i32 - 1).max(0);
+    let used = sizes.iter().map(|s| axis.main(s)).sum::<i32>() + gaps;
+    let max_cross = sizes.iter().map(|s| axis.cross(s)).max().unwrap_or(0);
+    linear_size(axis, view, e.space, used, max_cross)
+}
+
+pub fn layout_vbox<C>(pass: &mut LayoutEvent<C>) {
+    layout_linear(pass, Axis::Vertical);
+}
+
+pub fn layout_hbox<C>(pass: &mut LayoutEvent<C>) {
+    layout_linear(pass, Axis::Horizontal);
+}
+
+/// Set as the `measure` handler next to `layout_vbox`, so parents can measure the box
+/// without laying it out.
+pub fn measure_vbox<C>(e: &mut MeasureEvent<C>) -> Size {
+    measure_linear(e, Axis::Vertical)
+}
+
+pub fn measure_hbox<C>(e: &mut MeasureEvent<C>) -> Size {
+    measure_linear(e, Axis::Horizontal)
+}
+
+pub fn layout_std_panel<C>(pass: &mut LayoutEvent<C>) {
     if let Some(view) = pass.scene.get_view_mut(&pass.target) {
-        if view.v_flex == Resize {
-            view.bounds.size.h = pass.space.h;
-        }
-        if view.h_flex == Resize {
-            view.bounds.size.w = pass.space.w;
-        }
+        view.bounds.size = view.resolve_size(pass.space, view.bounds.size - view.padding);
         let space = view.bounds.size.clone() - view.padding;
         pass.layout_all_children(&pass.target.clone(), space);
     }
 }
 
+/// Set as the `measure` handler next to `layout_std_panel`. The panel fills the space on
+/// `Resize` axes and keeps its own size on the others, whatever its children.
+pub fn measure_std_panel<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    view.resolve_size(e.space, view.bounds.size - view.padding)
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
-    use crate::LayoutEvent;
     use crate::geom::{Bounds, Insets, Point, Size};
-    use crate::layouts::{layout_std_panel, layout_vbox};
+    use crate::label::make_text_block;
+    use crate::layouts::{
+        Axis, Justify, LinearLayoutState, distribute_space, layout_hbox, layout_std_panel,
+        layout_vbox, measure_hbox,
+    };
     use crate::scene::{Scene, layout_scene};
     use crate::test::MockDrawingContext;
     use crate::view::Align::Start;
-    use crate::view::{Align, Flex, View, ViewId};
+    use crate::view::{Align, Direction, Flex, View, ViewId};
+    use crate::{Handler, LayoutEvent, measure_view};
+    use alloc::boxed::Box;
+    use alloc::vec;
     use test_log::test;
     pub(crate) fn layout_button(layout: &mut LayoutEvent) {
         if let Some(view) = layout.scene.get_view_mut(&layout.target) {
@@ -209,7 +383,7 @@ pub(crate) mod tests {
         let button = View {
             name: "button1".into(),
             title: "abc".into(),
-            layout: Some(layout_button),
+            layout: Some(Handler::Fn(layout_button)),
             padding: Insets::new_same(10),
             ..Default::default()
         };
@@ -250,7 +424,7 @@ pub(crate) mod tests {
             v_flex: Flex::Resize,
             h_align: Start,
             v_align: Start,
-            layout: Some(layout_vbox),
+            layout: Some(Handler::Fn(layout_vbox)),
             ..Default::default()
         };
 
@@ -260,7 +434,7 @@ pub(crate) mod tests {
                 name: child1_id.clone(),
                 title: "ch1".into(),
                 h_align: Align::Start,
-                layout: Some(layout_button),
+                layout: Some(Handler::Fn(layout_button)),
                 ..Default::default()
             },
             &parent_id,
@@ -272,7 +446,7 @@ pub(crate) mod tests {
                 name: child2_id.clone(),
                 title: "ch2".into(),
                 h_align: Align::Center,
-                layout: Some(layout_button),
+                layout: Some(Handler::Fn(layout_button)),
                 ..Default::default()
             },
             &parent_id,
@@ -284,7 +458,7 @@ pub(crate) mod tests {
                 name: child3_id.clone(),
                 title: "ch3".into(),
                 h_align: Align::End,
-                layout: Some(layout_button),
+                layout: Some(Handler::Fn(layout_button)),
                 ..Default::default()
             },
             &parent_id,
@@ -297,7 +471,7 @@ pub(crate) mod tests {
                 title: "ch4".into(),
                 h_flex: Flex::Resize,
                 v_flex: Flex::Resize,
-                layout: Some(layout_std_panel),
+                layout: Some(Handler::Fn(layout_std_panel)),
                 ..Default::default()
             },
             &parent_id,
@@ -338,6 +512,366 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_flex_weights() {
+        assert_eq!(distribute_space(100, &[2, 1]), vec![66, 34]);
+        assert_eq!(distribute_space(10, &[1, 1, 1]), vec![3, 3, 4]);
+        assert_eq!(distribute_space(10, &[1, 1, 0]), vec![5, 5, 0]);
+        assert_eq!(distribute_space(10, &[0, 0]), vec![0, 0]);
+
+        // a 1:2 sidebar and content split, with a shrinking header above
+        let mut scene = Scene::new();
+        let parent: ViewId = "parent".into();
+        let header: ViewId = "header".into();
+        let sidebar: ViewId = "sidebar".into();
+        let content: ViewId = "content".into();
+        scene.add_view_to_root(View {
+            name: parent,
+            h_flex: Flex::Resize,
+            v_flex: Flex::Resize,
+            layout: Some(Handler::Fn(layout_vbox)),
+            ..Default::default()
+        });
+        let panel = |name: &ViewId, grow: u32| {
+            View {
+                name: *name,
+                h_flex: Flex::Resize,
+                v_flex: Flex::Resize,
+                layout: Some(Handler::Fn(layout_std_panel)),
+                ..Default::default()
+            }
+            .with_flex_grow(grow)
+        };
+        scene.add_view_to_parent(panel(&sidebar, 1), &parent);
+        scene.add_view_to_parent(panel(&content, 2), &parent);
+        let theme = MockDrawingContext::make_mock_theme();
+        layout_scene(&mut scene, &theme);
+        assert_eq!(view_bounds(&scene, &sidebar), Bounds::new(0, 0, 200, 66));
+        assert_eq!(view_bounds(&scene, &content), Bounds::new(0, 66, 200, 134));
+
+        // an intrinsic child too tall to fit gives up the overflow
+        scene.add_view_to_parent(
+            View {
+                name: header,
+                layout: Some(Handler::Fn(|e| {
+                    if let Some(view) = e.scene.get_view_mut(e.target) {
+                        view.bounds.size = Size::new(50, 250);
+                    }
+                })),
+                ..Default::default()
+            }
+            .with_flex_shrink(1),
+            &parent,
+        );
+        scene.mark_layout_dirty();
+        layout_scene(&mut scene, &theme);
+        assert_eq!(view_bounds(&scene, &header).size, Size::new(50, 200));
+        assert_eq!(view_bounds(&scene, &content).size, Size::new(200, 0));
+    }
+
+    /// Three fixed size children in a 100x50 container with 5px padding and a 5px gap.
+    /// Along the main axis they are 10, 20 and 30 long and on the cross axis 10, 20 and 30
+    /// wide, aligned start, center and end.
+    fn make_linear_scene(axis: Axis, state: LinearLayoutState, flex: Flex) -> Scene {
+        let mut scene = Scene::new();
+        let parent: ViewId = "parent".into();
+        scene.add_view_to_root(View {
+            name: parent,
+            padding: Insets::new_same(5),
+            h_flex: flex,
+            v_flex: flex,
+            state: Some(Box::new(state)),
+            layout: Some(Handler::Fn(match axis {
+                Axis::Horizontal => layout_hbox,
+                Axis::Vertical => layout_vbox,
+            })),
+            ..Default::default()
+        });
+        let kids = [
+            ("a", 10, Align::Start),
+            ("b", 20, Align::Center),
+            ("c", 30, Align::End),
+        ];
+        for (name, len, align) in kids {
+            let size = axis.size(len, len);
+            scene.add_view_to_parent(
+                View {
+                    name: name.into(),
+                    h_align: align,
+                    v_align: align,
+                    layout: None,
+                    ..Default::default()
+                }
+                .with_size(size.w, size.h),
+                &parent,
+            );
+        }
+        scene
+    }
+
+    #[test]
+    fn test_linear_layout() {
+        // main axis start of each child for the 20px of free space
+        let justified = [
+            (Justify::Start, [5, 20, 45]),
+            (Justify::Center, [15, 30, 55]),
+            (Justify::End, [25, 40, 65]),
+            (Justify::SpaceBetween, [5, 30, 65]),
+            (Justify::SpaceAround, [8, 29, 60]),
+        ];
+        // cross axis start and size of each child
+        let crossed = [
+            (false, [(5, 10), (15, 20), (15, 30)]),
+            (true, [(5, 40), (5, 40), (5, 40)]),
+        ];
+        let theme = MockDrawingContext::make_mock_theme();
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            for (justify, mains) in justified {
+                for (stretch, crosses) in crossed {
+                    let state = LinearLayoutState {
+                        gap: 5,
+                        justify,
+                        stretch,
+                    };
+                    let mut scene = make_linear_scene(axis, state, Flex::Resize);
+                    let mut pass = LayoutEvent {
+                        target: &"parent".into(),
+                        space: axis.size(100, 50),
+                        scene: &mut scene,
+                        theme: &theme,
+                    };
+                    layout_vbox_or_hbox(&mut pass, axis);
+                    let case = (axis, justify, stretch);
+                    assert_eq!(
+                        view_bounds(&scene, &"parent".into()).size,
+                        axis.size(100, 50),
+                        "{case:?}"
+                    );
+                    for (i, name) in ["a", "b", "c"].iter().enumerate() {
+                        let bounds = view_bounds(&scene, &(*name).into());
+                        let (cross, cross_len) = crosses[i];
+                        let main_len = (i as i32 + 1) * 10;
+                        let expected = match axis {
+                            Axis::Horizontal => Bounds::new(mains[i], cross, main_len, cross_len),
+                            Axis::Vertical => Bounds::new(cross, mains[i], cross_len, main_len),
+                        };
+                        assert_eq!(bounds, expected, "{case:?} child {name}");
+                    }
+                }
+            }
+        }
+    }
+
+    fn layout_vbox_or_hbox(pass: &mut LayoutEvent, axis: Axis) {
+        match axis {
+            Axis::Horizontal => layout_hbox(pass),
+            Axis::Vertical => layout_vbox(pass),
+        }
+    }
+
+    #[test]
+    fn test_linear_layout_intrinsic() {
+        // an intrinsic container wraps its children, gaps and padding
+        let theme = MockDrawingContext::make_mock_theme();
+        for axis in [Axis::Horizontal, Axis::Vertical] {
+            let state = LinearLayoutState {
+                gap: 5,
+                justify: Justify::End,
+                stretch: false,
+            };
+            let mut scene = make_linear_scene(axis, state, Flex::Intrinsic);
+            let mut pass = LayoutEvent {
+                target: &"parent".into(),
+                space: Size::new(200, 200),
+                scene: &mut scene,
+                theme: &theme,
+            };
+            layout_vbox_or_hbox(&mut pass, axis);
+            assert_eq!(
+                view_bounds(&scene, &"parent".into()).size,
+                axis.size(80, 40)
+            );
+            // nothing to justify, so the last child comes right after the others
+            let c = view_bounds(&scene, &"c".into());
+            let at = axis.size(45, 5);
+            assert_eq!(c.position, Point::new(at.w, at.h), "{axis:?}");
+        }
+    }
+
+    #[test]
+    fn test_margins_and_size_limits() {
+        let mut scene = Scene::new();
+        let parent: ViewId = "parent".into();
+        let button: ViewId = "button".into();
+        let panel: ViewId = "panel".into();
+        scene.add_view_to_root(View {
+            name: parent,
+            h_flex: Flex::Resize,
+            v_flex: Flex::Resize,
+            layout: Some(Handler::Fn(layout_vbox)),
+            ..Default::default()
+        });
+        // a small button grown to a minimum touch target
+        scene.add_view_to_parent(
+            View {
+                name: button,
+                title: "ab".into(),
+                h_align: Start,
+                layout: Some(Handler::Fn(layout_button)),
+                ..Default::default()
+            }
+            .with_margin(Insets::new_same(5))
+            .with_min_size(44, 44),
+            &parent,
+        );
+        // a flex panel that stops growing
+        scene.add_view_to_parent(
+            View {
+                name: panel,
+                h_flex: Flex::Resize,
+                v_flex: Flex::Resize,
+                layout: Some(Handler::Fn(layout_std_panel)),
+                ..Default::default()
+            }
+            .with_max_size(100, 50),
+            &parent,
+        );
+        let theme = MockDrawingContext::make_mock_theme();
+        layout_scene(&mut scene, &theme);
+        assert_eq!(view_bounds(&scene, &button), Bounds::new(5, 5, 44, 44));
+        assert_eq!(view_bounds(&scene, &panel), Bounds::new(50, 54, 100, 50));
+    }
+
+    #[test]
+    fn test_measure_then_arrange() {
+        let mut scene = Scene::new();
+        let row: ViewId = "row".into();
+        let text: ViewId = "text".into();
+        scene.add_view_to_root(View {
+            name: row,
+            h_flex: Flex::Resize,
+            layout: Some(Handler::Fn(layout_hbox)),
+            measure: Some(Handler::Fn(measure_hbox)),
+            ..Default::default()
+        });
+        scene.add_view_to_parent(make_standard_view(&"icon".into()).with_size(50, 20), &row);
+        scene.add_view_to_parent(
+            make_text_block("text", "the quick brown fox jumps over the lazy dog"),
+            &row,
+        );
+        let theme = MockDrawingContext::make_mock_theme();
+
+        // measuring reports the wrapped height for each width and changes nothing
+        assert_eq!(
+            measure_view(&scene, &theme, &row, Size::new(200, 200)),
+            Size::new(200, 30)
+        );
+        assert_eq!(
+            measure_view(&scene, &theme, &row, Size::new(110, 200)),
+            Size::new(110, 80)
+        );
+        assert_eq!(view_bounds(&scene, &text), Bounds::new(0, 0, 100, 100));
+
+        layout_scene(&mut scene, &theme);
+        assert_eq!(view_bounds(&scene, &row).size, Size::new(200, 30));
+        assert_eq!(view_bounds(&scene, &text), Bounds::new(50, 0, 150, 30));
+        assert_eq!(
+            view_bounds(&scene, &"icon".into()),
+            Bounds::new(0, 5, 50, 20)
+        );
+    }
+
+    #[test]
+    fn test_right_to_left() {
+        let mut scene = Scene::new();
+        let row: ViewId = "row".into();
+        let column: ViewId = "column".into();
+        scene.add_view_to_root(View {
+            name: row,
+            h_flex: Flex::Resize,
+            padding: Insets::new(0, 0, 0, 10),
+            layout: Some(Handler::Fn(layout_hbox)),
+            ..Default::default()
+        });
+        scene.add_view_to_parent(make_standard_view(&"a".into()).with_size(50, 20), &row);
+        scene.add_view_to_parent(make_standard_view(&"b".into()).with_size(30, 20), &row);
+        scene.add_view_to_root(
+            View {
+                name: column,
+                h_flex: Flex::Resize,
+                layout: Some(Handler::Fn(layout_vbox)),
+                ..Default::default()
+            }
+            .with_direction(Direction::Rtl),
+        );
+        let mut start = make_standard_view(&"start".into()).with_size(40, 20);
+        start.h_align = Start;
+        scene.add_view_to_parent(start, &column);
+        let theme = MockDrawingContext::make_mock_theme();
+        layout_scene(&mut scene, &theme);
+
+        // only the column is right to left, so its start is on the right
+        assert_eq!(view_bounds(&scene, &"a".into()).x(), 10);
+        assert_eq!(view_bounds(&scene, &"start".into()).x(), 160);
+
+        // the whole scene mirrors the row, inside its padding
+        scene.set_direction(Direction::Rtl);
+        layout_scene(&mut scene, &theme);
+        assert_eq!(view_bounds(&scene, &"a".into()).x(), 150);
+        assert_eq!(view_bounds(&scene, &"b".into()).x(), 120);
+        assert_eq!(view_bounds(&scene, &"start".into()).x(), 160);
+    }
+
+    #[test]
+    fn test_percent_and_aspect_ratio() {
+        let mut scene = Scene::new();
+        let row: ViewId = "row".into();
+        scene.add_view_to_root(View {
+            name: row,
+            h_flex: Flex::Resize,
+            layout: Some(Handler::Fn(layout_hbox)),
+            ..Default::default()
+        });
+        scene.add_view_to_parent(
+            make_standard_view(&"image".into())
+                .with_size(10, 20)
+                .with_percent_size(Some(40), None),
+            &row,
+        );
+        scene.add_view_to_parent(
+            View {
+                name: "swatch".into(),
+                h_flex: Flex::Resize,
+                ..Default::default()
+            }
+            .with_aspect_ratio(1, 1),
+            &row,
+        );
+        scene.add_view_to_root(
+            make_standard_view(&"banner".into())
+                .with_percent_size(None, Some(25))
+                .with_aspect_ratio(2, 1),
+        );
+        let theme = MockDrawingContext::make_mock_theme();
+        layout_scene(&mut scene, &theme);
+
+        // the image takes 40% of the row and the swatch stays square in the rest
+        assert_eq!(
+            view_bounds(&scene, &"image".into()),
+            Bounds::new(0, 50, 80, 20)
+        );
+        assert_eq!(
+            view_bounds(&scene, &"swatch".into()),
+            Bounds::new(80, 0, 120, 120)
+        );
+        assert_eq!(view_bounds(&scene, &row).size, Size::new(200, 120));
+        // the width follows a percentage height
+        assert_eq!(
+            view_bounds(&scene, &"banner".into()).size,
+            Size::new(100, 50)
+        );
+    }
+
     pub fn make_standard_view(name: &ViewId) -> View {
         View {
             name: name.clone(),
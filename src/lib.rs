@@ -3,50 +3,229 @@
 extern crate alloc;
 extern crate core;
 
+use crate::animation::AnimationId;
 use crate::geom::Size;
 use crate::scene::Scene;
-use crate::view::ViewId;
+use crate::timer::TimerId;
+use crate::view::{Direction, ViewId};
+use alloc::rc::Rc;
 use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt::{Debug, Formatter};
 use embedded_graphics::mono_font::MonoFont;
 use embedded_graphics::pixelcolor::Rgb565;
 use geom::{Bounds, Point};
 use gfx::DrawingContext;
-use view::View;
+use view::{Flex, View};
 
+pub mod accelerator;
+pub mod animation;
 pub mod button;
 pub mod device;
+pub mod flow;
 pub mod geom;
 pub mod gfx;
 pub mod grid;
+pub mod input;
 pub mod label;
 pub mod layouts;
 pub mod list_view;
+pub mod navigator;
 pub mod panel;
+pub mod recorder;
 pub mod scene;
+pub mod scroll_view;
+pub mod stack;
 pub mod tabbed_panel;
 pub mod test;
 pub mod text_input;
+pub mod timer;
 pub mod toggle_button;
 pub mod toggle_group;
 pub mod util;
 pub mod view;
 
-pub struct DrawEvent<'a> {
+pub struct DrawEvent<'a, C = ()> {
     pub ctx: &'a mut dyn DrawingContext,
     pub theme: &'a Theme,
     pub focused: &'a Option<ViewId>,
-    pub view: &'a mut View,
+    pub pressed: &'a Option<ViewId>,
+    pub hovered: &'a Option<ViewId>,
+    pub view: &'a mut View<C>,
     pub bounds: &'a Bounds,
+    /// False if this view or one of its ancestors is disabled. The theme already has the
+    /// disabled colors swapped in.
+    pub enabled: bool,
+    /// The direction of the view, from the nearest ancestor that sets one.
+    pub direction: Direction,
 }
 
+impl<C> DrawEvent<'_, C> {
+    pub fn is_pressed(&self) -> bool {
+        self.pressed.as_ref() == Some(&self.view.name)
+    }
+    pub fn is_hovered(&self) -> bool {
+        self.hovered.as_ref() == Some(&self.view.name)
+    }
+    /// The background for a button-like view, showing the pressed and hover states.
+    pub fn button_bg(&self) -> &Rgb565 {
+        if self.is_pressed() {
+            &self.theme.pressed_bg
+        } else if self.is_hovered() {
+            &self.theme.hover_bg
+        } else {
+            &self.theme.bg
+        }
+    }
+}
+
+/// What a view reports back to the app after handling an event.
 #[derive(Debug, Clone)]
 pub enum Action {
+    /// The view was activated, such as a button being tapped.
     Generic,
+    /// An app defined command.
     Command(String),
+    /// A navigator went back a screen.
+    Back,
+    /// An item was picked by index, such as a list row or a toggle group segment.
+    Selected(usize),
+    /// A two state control changed to the new state.
+    Toggled(bool),
+    /// A numeric control changed to the new value.
+    ValueChanged(i32),
+    /// The text of an input was edited.
+    TextChanged(String),
+    /// The user confirmed an input, such as pressing return in a text field.
+    Submitted(String),
+    /// Any other payload. Use [`Action::custom`] and [`Action::payload`] to wrap and unwrap it.
+    Custom(Rc<dyn Any>),
+}
+
+impl Action {
+    pub fn custom<T: 'static>(value: T) -> Action {
+        Action::Custom(Rc::new(value))
+    }
+    pub fn payload<T: 'static>(&self) -> Option<&T> {
+        if let Action::Custom(value) = self {
+            return value.downcast_ref::<T>();
+        }
+        None
+    }
+}
+pub type DrawFn<C = ()> = fn(event: &mut DrawEvent<C>);
+pub type LayoutFn<C = ()> = fn(layout: &mut LayoutEvent<C>);
+pub type MeasureFn<C = ()> = fn(event: &mut MeasureEvent<C>) -> Size;
+pub type InputFn<C = ()> = fn(event: &mut GuiEvent<C>) -> Option<Action>;
+pub type CallbackFn<C = ()> = fn(event: &mut GuiEvent<C>);
+pub type LifecycleFn<C = ()> = fn(event: &mut LifecycleEvent<C>);
+
+/// A view or app handler. `Fn` is a plain function pointer, which costs nothing extra and
+/// is the right choice on memory constrained targets. `Closure` can capture state, at the
+/// cost of a heap allocation.
+pub enum Handler<F: Copy, C: ?Sized> {
+    Fn(F),
+    Closure(Rc<C>),
+}
+
+pub type DrawHandler<C = ()> = Handler<DrawFn<C>, dyn Fn(&mut DrawEvent<C>)>;
+pub type LayoutHandler<C = ()> = Handler<LayoutFn<C>, dyn Fn(&mut LayoutEvent<C>)>;
+pub type MeasureHandler<C = ()> = Handler<MeasureFn<C>, dyn Fn(&mut MeasureEvent<C>) -> Size>;
+pub type InputHandler<C = ()> = Handler<InputFn<C>, dyn Fn(&mut GuiEvent<C>) -> Option<Action>>;
+pub type Callback<C = ()> = Handler<CallbackFn<C>, dyn Fn(&mut GuiEvent<C>)>;
+pub type LifecycleHandler<C = ()> = Handler<LifecycleFn<C>, dyn Fn(&mut LifecycleEvent<C>)>;
+
+impl<F: Copy, C: ?Sized> Clone for Handler<F, C> {
+    fn clone(&self) -> Self {
+        match self {
+            Handler::Fn(f) => Handler::Fn(*f),
+            Handler::Closure(c) => Handler::Closure(c.clone()),
+        }
+    }
+}
+
+impl<F: Copy, C: ?Sized> Debug for Handler<F, C> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Handler::Fn(_) => write!(f, "Handler::Fn"),
+            Handler::Closure(_) => write!(f, "Handler::Closure"),
+        }
+    }
+}
+
+impl<C> DrawHandler<C> {
+    pub fn closure(f: impl Fn(&mut DrawEvent<C>) + 'static) -> Self {
+        Handler::Closure(Rc::new(f))
+    }
+    pub fn call(&self, event: &mut DrawEvent<C>) {
+        match self {
+            Handler::Fn(f) => f(event),
+            Handler::Closure(f) => f(event),
+        }
+    }
+}
+
+impl<C> LayoutHandler<C> {
+    pub fn closure(f: impl Fn(&mut LayoutEvent<C>) + 'static) -> Self {
+        Handler::Closure(Rc::new(f))
+    }
+    pub fn call(&self, event: &mut LayoutEvent<C>) {
+        match self {
+            Handler::Fn(f) => f(event),
+            Handler::Closure(f) => f(event),
+        }
+    }
+}
+
+impl<C> MeasureHandler<C> {
+    pub fn closure(f: impl Fn(&mut MeasureEvent<C>) -> Size + 'static) -> Self {
+        Handler::Closure(Rc::new(f))
+    }
+    pub fn call(&self, event: &mut MeasureEvent<C>) -> Size {
+        match self {
+            Handler::Fn(f) => f(event),
+            Handler::Closure(f) => f(event),
+        }
+    }
+}
+
+impl<C> InputHandler<C> {
+    pub fn closure(f: impl Fn(&mut GuiEvent<C>) -> Option<Action> + 'static) -> Self {
+        Handler::Closure(Rc::new(f))
+    }
+    pub fn call(&self, event: &mut GuiEvent<C>) -> Option<Action> {
+        match self {
+            Handler::Fn(f) => f(event),
+            Handler::Closure(f) => f(event),
+        }
+    }
+}
+
+impl<C> Callback<C> {
+    pub fn closure(f: impl Fn(&mut GuiEvent<C>) + 'static) -> Self {
+        Handler::Closure(Rc::new(f))
+    }
+    pub fn call(&self, event: &mut GuiEvent<C>) {
+        match self {
+            Handler::Fn(f) => f(event),
+            Handler::Closure(f) => f(event),
+        }
+    }
+}
+
+impl<C> LifecycleHandler<C> {
+    pub fn closure(f: impl Fn(&mut LifecycleEvent<C>) + 'static) -> Self {
+        Handler::Closure(Rc::new(f))
+    }
+    pub fn call(&self, event: &mut LifecycleEvent<C>) {
+        match self {
+            Handler::Fn(f) => f(event),
+            Handler::Closure(f) => f(event),
+        }
+    }
 }
-pub type DrawFn = fn(event: &mut DrawEvent);
-pub type LayoutFn = fn(layout: &mut LayoutEvent);
-pub type InputFn = fn(event: &mut GuiEvent) -> Option<Action>;
 
 #[derive(Debug)]
 pub struct Theme {
@@ -55,68 +234,398 @@ pub struct Theme {
     pub panel_bg: Rgb565,
     pub selected_bg: Rgb565,
     pub selected_fg: Rgb565,
+    pub disabled_bg: Rgb565,
+    pub disabled_fg: Rgb565,
+    pub pressed_bg: Rgb565,
+    pub hover_bg: Rgb565,
     pub font: MonoFont<'static>,
     pub bold_font: MonoFont<'static>,
 }
 
-pub type Callback = fn(event: &mut GuiEvent);
+impl Theme {
+    /// The theme used for disabled views, with every color swapped for a disabled one.
+    pub fn disabled(&self) -> Theme {
+        Theme {
+            bg: self.disabled_bg,
+            fg: self.disabled_fg,
+            panel_bg: self.panel_bg,
+            selected_bg: self.disabled_fg,
+            selected_fg: self.disabled_bg,
+            disabled_bg: self.disabled_bg,
+            disabled_fg: self.disabled_fg,
+            pressed_bg: self.disabled_bg,
+            hover_bg: self.disabled_bg,
+            font: self.font,
+            bold_font: self.bold_font,
+        }
+    }
+}
 
-#[derive(Debug, Clone)]
-pub enum KeyboardAction {
+/// The logical key of a keyboard event, independent of any text it produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// A printable key. Holds the unshifted character, e.g. `'a'` for both `a` and `A`.
+    Char(char),
     Left,
     Right,
     Up,
     Down,
     Backspace,
-    Return,
     Delete,
+    Return,
+    Tab,
+    Escape,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    /// Function keys F1 to F24.
+    F(u8),
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers {
+        shift: false,
+        ctrl: false,
+        alt: false,
+        meta: false,
+    };
+    pub const fn shift() -> Modifiers {
+        Modifiers {
+            shift: true,
+            ..Modifiers::NONE
+        }
+    }
+    pub const fn ctrl() -> Modifiers {
+        Modifiers {
+            ctrl: true,
+            ..Modifiers::NONE
+        }
+    }
+    pub const fn alt() -> Modifiers {
+        Modifiers {
+            alt: true,
+            ..Modifiers::NONE
+        }
+    }
+    /// True if a modifier that turns a key into a shortcut (ctrl, alt or meta) is held.
+    pub fn is_command(&self) -> bool {
+        self.ctrl || self.alt || self.meta
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyState {
+    Pressed,
+    Released,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyEvent {
+    pub key: Key,
+    /// The text the key produces, if any. `None` for navigation keys and releases.
+    pub text: Option<char>,
+    pub modifiers: Modifiers,
+    pub state: KeyState,
+    /// True when the event was generated by the key being held down.
+    pub repeat: bool,
+}
+
+impl KeyEvent {
+    pub fn pressed(key: Key) -> KeyEvent {
+        KeyEvent {
+            key,
+            text: None,
+            modifiers: Modifiers::NONE,
+            state: KeyState::Pressed,
+            repeat: false,
+        }
+    }
+    pub fn released(key: Key) -> KeyEvent {
+        KeyEvent {
+            state: KeyState::Released,
+            ..KeyEvent::pressed(key)
+        }
+    }
+    /// A key press that types `ch`.
+    pub fn text(ch: char) -> KeyEvent {
+        let mut lower = ch.to_lowercase();
+        let key = match (lower.next(), lower.next()) {
+            (Some(lower), None) => lower,
+            _ => ch,
+        };
+        KeyEvent {
+            text: Some(ch),
+            modifiers: if ch != key {
+                Modifiers::shift()
+            } else {
+                Modifiers::NONE
+            },
+            ..KeyEvent::pressed(Key::Char(key))
+        }
+    }
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> KeyEvent {
+        self.modifiers = modifiers;
+        self
+    }
+    pub fn with_repeat(mut self, repeat: bool) -> KeyEvent {
+        self.repeat = repeat;
+        self
+    }
+    pub fn is_pressed(&self) -> bool {
+        self.state == KeyState::Pressed
+    }
+    /// The text to insert for this event, ignoring releases and shortcut chords.
+    pub fn typed_text(&self) -> Option<char> {
+        if self.is_pressed() && !self.modifiers.is_command() {
+            self.text
+        } else {
+            None
+        }
+    }
 }
+
 #[derive(Debug, Clone)]
 pub enum EventType {
     Generic,
     Unknown,
     Tap(Point),
     Scroll(i32, i32),
-    Keyboard(u8),
-    KeyboardAction(KeyboardAction),
+    Key(KeyEvent),
+    Timer(TimerId),
+    AnimationDone(AnimationId),
     Action(),
 }
 #[derive(Debug)]
-pub struct GuiEvent<'a> {
-    pub scene: &'a mut Scene,
+pub struct GuiEvent<'a, C = ()> {
+    pub scene: &'a mut Scene<C>,
     pub target: &'a ViewId,
     pub event_type: EventType,
     pub action: Option<Action>,
+    /// App provided state, such as settings or a hardware handle, passed to the call that
+    /// dispatched the event. Its type is the context type of the scene.
+    pub context: &'a mut C,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lifecycle {
+    /// Added to the scene.
+    Mounted,
+    /// About to be removed from the scene.
+    Unmounted,
+    Shown,
+    Hidden,
+    Focused,
+    Unfocused,
+}
+
+#[derive(Debug)]
+pub struct LifecycleEvent<'a, C = ()> {
+    pub scene: &'a mut Scene<C>,
+    pub target: &'a ViewId,
+    pub lifecycle: Lifecycle,
 }
 
 #[derive(Debug)]
-pub struct LayoutEvent<'a> {
-    pub scene: &'a mut Scene,
+pub struct LayoutEvent<'a, C = ()> {
+    pub scene: &'a mut Scene<C>,
     pub target: &'a ViewId,
     pub space: Size,
     pub theme: &'a Theme,
 }
 
-impl<'a> LayoutEvent<'a> {
+impl<'a, C> LayoutEvent<'a, C> {
     pub(crate) fn layout_all_children(&mut self, name: &ViewId, space: Size) {
         let fixed_kids = self.scene.get_children_ids(name);
         for kid in &fixed_kids {
             self.layout_child(kid, space);
         }
     }
+    /// Lay out a child in `available_space`, which includes the child's margin. The
+    /// result follows the child's percentage and aspect ratio sizes, and is kept within
+    /// its min and max size.
     pub(crate) fn layout_child(&mut self, kid: &ViewId, available_space: Size) {
-        if let Some(view) = self.scene.get_view_mut(kid) {
-            if let Some(layout) = view.layout {
+        self.scene.layout_spaces.insert(*kid, available_space);
+        let Some(space) = self
+            .scene
+            .get_view(kid)
+            .map(|view| view.available_space(available_space))
+        else {
+            return;
+        };
+        self.layout_view(kid, space);
+    }
+    /// Run a child's layout or measure handler for `space`, which is already inside its
+    /// margin and percentage.
+    fn layout_view(&mut self, kid: &ViewId, space: Size) {
+        let Some(view) = self.scene.get_view(kid) else {
+            return;
+        };
+        match (view.layout.clone(), view.measure.clone()) {
+            (Some(layout), _) => {
                 let mut pass = LayoutEvent {
                     target: kid,
-                    space: available_space,
+                    space,
                     scene: self.scene,
                     theme: self.theme,
                 };
-                layout(&mut pass);
+                layout.call(&mut pass);
+            }
+            (None, Some(measure)) => {
+                let size = measure.call(&mut MeasureEvent {
+                    scene: self.scene,
+                    target: kid,
+                    space,
+                    theme: self.theme,
+                });
+                if let Some(view) = self.scene.get_view_mut(kid) {
+                    view.bounds.size = size;
+                }
+            }
+            (None, None) => {}
+        }
+        if let Some(view) = self.scene.get_view_mut(kid) {
+            view.bounds.size = view.clamp_size(view.constrain_size(view.bounds.size, space));
+        }
+    }
+    /// How big a child wants to be in `available_space`, including its margin. A child that
+    /// can't be measured, because it or a view inside it only has a layout handler, is laid
+    /// out to find out, and then the bounds in its subtree are put back.
+    pub fn measure_child(&mut self, kid: &ViewId, available_space: Size) -> Size {
+        if is_measurable(self.scene, kid) {
+            return measure_view(self.scene, self.theme, kid, available_space);
+        }
+        let mut saved = vec![];
+        save_layout(self.scene, kid, &mut saved);
+        self.layout_child(kid, available_space);
+        let size = self
+            .scene
+            .get_view(kid)
+            .map(|view| view.outer_size())
+            .unwrap_or(Size::new(0, 0));
+        for (name, bounds, space) in saved {
+            if let Some(view) = self.scene.get_view_mut(&name) {
+                view.bounds = bounds;
             }
+            match space {
+                Some(space) => self.scene.layout_spaces.insert(name, space),
+                None => self.scene.layout_spaces.remove(&name),
+            };
+        }
+        size
+    }
+    /// Lay out a child and place it at `bounds`, which doesn't include its margin. This
+    /// commits the size the parent settled on after measuring.
+    pub fn arrange_child(&mut self, kid: &ViewId, bounds: Bounds) {
+        let Some(view) = self.scene.get_view(kid) else {
+            return;
+        };
+        let space = view.clamp_size(bounds.size);
+        // the space of a view sized by percentage is only known to its parent, so a
+        // relayout has to start there
+        if view.h_percent.is_none() && view.v_percent.is_none() {
+            let outer = bounds.size + view.margin;
+            self.scene.layout_spaces.insert(*kid, outer);
+        } else {
+            self.scene.layout_spaces.remove(kid);
         }
+        self.layout_view(kid, space);
+        if let Some(view) = self.scene.get_view_mut(kid) {
+            view.bounds = Bounds::new_from(bounds.position, view.clamp_size(bounds.size));
+        }
+    }
+}
+
+/// Asks a view how big it would be in some space. The scene can't be changed, so measuring
+/// has no side effects and a parent can try out several sizes before arranging.
+pub struct MeasureEvent<'a, C = ()> {
+    pub scene: &'a Scene<C>,
+    pub target: &'a ViewId,
+    /// The space for the view itself, already inside its margin.
+    pub space: Size,
+    pub theme: &'a Theme,
+}
+
+impl<C> MeasureEvent<'_, C> {
+    /// How big a child wants to be in `available_space`, including its margin.
+    pub fn measure_child(&self, kid: &ViewId, available_space: Size) -> Size {
+        measure_view(self.scene, self.theme, kid, available_space)
+    }
+}
+
+/// Views with neither handler keep their size, apart from filling the space on `Resize`
+/// axes, so they can be measured. So can views with a measure handler, as long as
+/// everything inside them can be.
+fn is_measurable<C>(scene: &Scene<C>, name: &ViewId) -> bool {
+    let Some(view) = scene.get_view(name) else {
+        return true;
+    };
+    view.layout.is_none()
+        || (view.measure.is_some()
+            && scene
+                .get_children_ids(name)
+                .iter()
+                .all(|kid| is_measurable(scene, kid)))
+}
+
+/// The bounds and layout space of every view in a subtree, to put back after measuring by
+/// laying out.
+fn save_layout<C>(
+    scene: &Scene<C>,
+    name: &ViewId,
+    saved: &mut Vec<(ViewId, Bounds, Option<Size>)>,
+) {
+    if let Some(view) = scene.get_view(name) {
+        saved.push((*name, view.bounds, scene.layout_spaces.get(name).copied()));
     }
+    for kid in scene.get_children_ids(name) {
+        save_layout(scene, &kid, saved);
+    }
+}
+
+/// The size a view wants in `available_space`, including its margin. Inside a measure
+/// handler, views that only have a layout handler report the size from their last layout,
+/// since running it would change the scene.
+pub(crate) fn measure_view<C>(
+    scene: &Scene<C>,
+    theme: &Theme,
+    kid: &ViewId,
+    available_space: Size,
+) -> Size {
+    let Some(view) = scene.get_view(kid) else {
+        return Size::new(0, 0);
+    };
+    let space = view.available_space(available_space);
+    let size = match &view.measure {
+        Some(measure) => measure.call(&mut MeasureEvent {
+            scene,
+            target: kid,
+            space,
+            theme,
+        }),
+        None => Size::new(
+            if view.h_flex == Flex::Resize {
+                space.w
+            } else {
+                view.bounds.size.w
+            },
+            if view.v_flex == Flex::Resize {
+                space.h
+            } else {
+                view.bounds.size.h
+            },
+        ),
+    };
+    view.clamp_size(view.constrain_size(size, space)) + view.margin
 }
 
 #[cfg(test)]
@@ -124,13 +633,16 @@ mod tests {
     use super::*;
     use crate::button::make_button;
     use crate::gfx::TextStyle;
-    use crate::scene::{click_at, draw_scene, event_at_focused, pick_at};
+    use crate::scene::{
+        click_at, draw_scene, event_at_focused, hover_at, layout_scene, pick_at, press_at,
+    };
     use crate::test::MockDrawingContext;
     use crate::view::Align;
     use alloc::boxed::Box;
     use alloc::string::ToString;
     use alloc::vec;
     use alloc::vec::Vec;
+    use core::cell::Cell;
     use log::{LevelFilter, info};
     use std::sync::Once;
     use test_log::test;
@@ -143,7 +655,9 @@ mod tests {
             title: name.to_string(),
             bounds: Bounds::new(0, 0, 10, 10),
             visible: true,
-            draw: Some(|e| e.ctx.fill_rect(&e.view.bounds, &e.theme.bg)),
+            draw: Some(Handler::Fn(|e| {
+                e.ctx.fill_rect(&e.view.bounds, &e.theme.bg)
+            })),
             input: None,
             state: None,
             layout: None,
@@ -171,12 +685,12 @@ mod tests {
             title: name.to_string(),
             bounds,
             visible: true,
-            draw: Some(|e| {
+            draw: Some(Handler::Fn(|e| {
                 e.ctx.fill_rect(&e.view.bounds, &e.theme.panel_bg);
-            }),
+            })),
             input: None,
             state: None,
-            layout: Some(layout_vbox),
+            layout: Some(Handler::Fn(layout_vbox)),
             ..Default::default()
         }
     }
@@ -190,14 +704,14 @@ mod tests {
             title: name.to_string(),
             bounds: Bounds::new(0, 0, 20, 20),
             visible: true,
-            draw: Some(|e| {
+            draw: Some(Handler::Fn(|e| {
                 if let Some(state) = &mut e.view.state {
                     if let Some(state) = state.downcast_mut::<TestButtonState>() {
                         state.drawn = true;
                     }
                 }
-            }),
-            input: Some(|e| {
+            })),
+            input: Some(Handler::Fn(|e| {
                 if let Some(view) = e.scene.get_view_mut(e.target) {
                     if let Some(state) = &mut view.state {
                         if let Some(state) = state.downcast_mut::<TestButtonState>() {
@@ -206,7 +720,7 @@ mod tests {
                     }
                 }
                 None
-            }),
+            })),
             state: Some(Box::new(TestButtonState {
                 drawn: false,
                 got_input: false,
@@ -224,18 +738,20 @@ mod tests {
             state: None,
             draw: None,
             layout: None,
-            input: Some(|e| {
-                match e.event_type {
-                    EventType::Keyboard(key) => {
-                        info!("got a keyboard event {}", key);
-                        if let Some(view) = e.scene.get_view_mut(e.target) {
-                            view.title.push(key as char)
+            input: Some(Handler::Fn(|e| {
+                match &e.event_type {
+                    EventType::Key(key) => {
+                        info!("got a keyboard event {:?}", key);
+                        if let Some(ch) = key.typed_text()
+                            && let Some(view) = e.scene.get_view_mut(e.target)
+                        {
+                            view.title.push(ch)
                         }
                     }
                     _ => info!("ignoring other event"),
                 };
                 None
-            }),
+            })),
             ..Default::default()
         }
     }
@@ -252,7 +768,7 @@ mod tests {
             title: name.to_string(),
             bounds: Bounds::new(0, 0, 30, 20),
             visible: true,
-            draw: Some(draw_label_view),
+            draw: Some(Handler::Fn(draw_label_view)),
             input: None,
             state: None,
             layout: None,
@@ -336,6 +852,44 @@ mod tests {
         );
     }
     #[test]
+    fn test_measure_by_layout() {
+        let parent: ViewId = "parent".into();
+        let button: ViewId = "button1".into();
+        let theme = MockDrawingContext::make_mock_theme();
+        let mut scene: Scene = Scene::new();
+        scene.add_view(make_vbox(&parent, Bounds::new(10, 10, 100, 100)));
+        scene.add_view_to_parent(make_test_button(&button), &parent);
+        layout_scene(&mut scene, &theme);
+        let before = (
+            scene.get_view_bounds(&parent),
+            scene.get_view_bounds(&button),
+        );
+        // the button only has a layout handler, so measuring lays it out and puts it back
+        let root = scene.root_id();
+        let mut pass = LayoutEvent {
+            scene: &mut scene,
+            target: &root,
+            theme: &theme,
+            space: Size::new(300, 300),
+        };
+        let size = pass.measure_child(&parent, Size::new(300, 300));
+        assert_eq!(
+            (
+                scene.get_view_bounds(&parent),
+                scene.get_view_bounds(&button)
+            ),
+            before
+        );
+        let mut pass = LayoutEvent {
+            scene: &mut scene,
+            target: &root,
+            theme: &theme,
+            space: Size::new(300, 300),
+        };
+        pass.layout_child(&parent, Size::new(300, 300));
+        assert_eq!(scene.get_view(&parent).unwrap().outer_size(), size);
+    }
+    #[test]
     fn test_repaint() {
         let mut scene = Scene::new();
         // add panel
@@ -350,27 +904,124 @@ mod tests {
         assert_eq!(scene.dirty, false);
     }
     #[test]
+    fn test_disabled_views() {
+        let mut scene: Scene = Scene::new();
+        let panel: ViewId = "panel".into();
+        let first: ViewId = "first".into();
+        let second: ViewId = "second".into();
+        scene.add_view_to_root(make_simple_view(&panel).with_size(100, 100));
+        scene.add_view_to_parent(make_button(&first, "First").position_at(0, 0), &panel);
+        scene.add_view_to_parent(make_button(&second, "Second").position_at(0, 50), &panel);
+        // record what the draw function sees
+        let seen = Rc::new(Cell::new(None));
+        let seen2 = seen.clone();
+        if let Some(view) = scene.get_view_mut(&second) {
+            view.draw = Some(DrawHandler::closure(move |e| {
+                seen2.set(Some((e.enabled, e.theme.fg)));
+            }));
+        }
+
+        // disabled views can't be tapped or focused
+        scene.set_enabled(&second, false);
+        assert!(click_at(&mut scene, &vec![], Point::new(5, 55), &mut ()).is_none());
+        assert_eq!(scene.focus_next(), Some(first));
+        assert_eq!(scene.focus_next(), Some(first));
+        scene.set_enabled(&second, true);
+        assert_eq!(scene.focus_next(), Some(second));
+        assert_eq!(scene.focus_prev(), Some(first));
+
+        // disabling a parent disables the subtree and drops focus
+        scene.set_enabled(&panel, false);
+        assert!(!scene.is_enabled(&first));
+        assert_eq!(scene.get_focused(), None);
+        assert!(click_at(&mut scene, &vec![], Point::new(5, 5), &mut ()).is_none());
+        assert_eq!(scene.focus_next(), None);
+        repaint(&mut scene);
+        let theme = MockDrawingContext::make_mock_theme();
+        assert_eq!(seen.get(), Some((false, theme.disabled_fg)));
+    }
+    #[test]
+    fn test_pressed_and_hover() {
+        let mut scene: Scene = Scene::new();
+        let button: ViewId = "button".into();
+        scene.add_view_to_root(make_button(&button, "Go").position_at(10, 10));
+        let seen = Rc::new(Cell::new(None));
+        let seen2 = seen.clone();
+        if let Some(view) = scene.get_view_mut(&button) {
+            view.draw = Some(DrawHandler::closure(move |e| {
+                seen2.set(Some(*e.button_bg()));
+            }));
+        }
+        let theme = MockDrawingContext::make_mock_theme();
+        repaint(&mut scene);
+
+        hover_at(&mut scene, Point::new(15, 15));
+        assert_eq!(scene.get_hovered(), Some(button));
+        assert!(!scene.dirty_rect.is_empty());
+        repaint(&mut scene);
+        assert_eq!(seen.get(), Some(theme.hover_bg));
+
+        // pressed wins over hover until the tap is delivered
+        press_at(&mut scene, Point::new(15, 15));
+        assert_eq!(scene.get_pressed(), Some(button));
+        repaint(&mut scene);
+        assert_eq!(seen.get(), Some(theme.pressed_bg));
+        assert!(click_at(&mut scene, &vec![], Point::new(15, 15), &mut ()).is_some());
+        assert_eq!(scene.get_pressed(), None);
+        repaint(&mut scene);
+        assert_eq!(seen.get(), Some(theme.hover_bg));
+
+        hover_at(&mut scene, Point::new(300, 300));
+        assert_eq!(scene.get_hovered(), None);
+        repaint(&mut scene);
+        assert_eq!(seen.get(), Some(theme.bg));
+    }
+    #[test]
+    fn test_app_context() {
+        struct Settings {
+            volume: i32,
+        }
+        let mut scene: Scene<Settings> = Scene::new();
+        let mut button: View<Settings> =
+            make_button(&"louder".into(), "Louder").position_at(10, 10);
+        button.input = Some(Handler::Fn(|e| {
+            e.context.volume += 1;
+            Some(Action::Generic)
+        }));
+        scene.add_view_to_root(button);
+        let mut settings = Settings { volume: 3 };
+        // app handlers get the same context after the view
+        let handlers: Vec<Callback<Settings>> = vec![Handler::Fn(|e| e.context.volume *= 10)];
+        click_at(&mut scene, &handlers, Point::new(15, 15), &mut settings);
+        assert_eq!(settings.volume, 40);
+        scene.set_focused(&"louder".into());
+        let enter = EventType::Key(KeyEvent::pressed(Key::Return));
+        event_at_focused(&mut scene, &enter, &mut settings);
+        assert_eq!(settings.volume, 41);
+    }
+    #[test]
     fn test_events() {
         let mut scene: Scene = Scene::new();
         let mut handlers: Vec<Callback> = vec![];
-        handlers.push(|event| {
+        handlers.push(Handler::Fn(|event| {
             info!("got an event {:?}", event);
             if let Some(view) = event.scene.get_view_mut(event.target) {
                 view.visible = false;
             }
             event.scene.dirty = true;
-        });
-        handlers.push(|event| {
+        }));
+        // closures can capture app state
+        let count = Rc::new(Cell::new(0));
+        let seen = count.clone();
+        handlers.push(Callback::closure(move |event| {
             info!("got another event {:?}", event);
-            if let Some(view) = event.scene.get_view_mut(event.target) {
-                view.visible = false;
-            }
-            event.scene.dirty = true;
+            seen.set(seen.get() + 1);
             info!("the action is {:?}", event.action);
-        });
+        }));
         assert_eq!(scene.get_view(&"root".into()).unwrap().visible, true);
-        click_at(&mut scene, &handlers, Point::new(5, 5));
+        click_at(&mut scene, &handlers, Point::new(5, 5), &mut ());
         assert_eq!(scene.get_view(&"root".into()).unwrap().visible, false);
+        assert_eq!(count.get(), 1);
     }
     fn handle_toggle_button_input(event: &mut GuiEvent) -> Option<Action> {
         // info!("view clicked {:?}", event.event_type);
@@ -388,7 +1039,7 @@ mod tests {
             title: String::from("Off"),
             visible: true,
             bounds: Bounds::new(10, 10, 20, 20),
-            draw: Some(|e| {
+            draw: Some(Handler::Fn(|e| {
                 if let Some(state) = &e.view.state {
                     if let Some(state) = state.downcast_ref::<String>() {
                         if state == "enabled" {
@@ -406,8 +1057,8 @@ mod tests {
                         }
                     }
                 }
-            }),
-            input: Some(handle_toggle_button_input),
+            })),
+            input: Some(Handler::Fn(handle_toggle_button_input)),
             state: Some(Box::new(String::from("disabled"))),
             layout: None,
             ..Default::default()
@@ -430,7 +1081,7 @@ mod tests {
         );
         // click at
         let handlers = vec![];
-        click_at(&mut scene, &handlers, Point::new(15, 15));
+        click_at(&mut scene, &handlers, Point::new(15, 15), &mut ());
         // confirm toggle button state has changed to enabled
         assert_eq!(
             &scene
@@ -473,17 +1124,17 @@ mod tests {
         assert_eq!(was_button_drawn(&mut scene, &"button2".into()), false);
 
         let mut handlers: Vec<Callback> = vec![];
-        handlers.push(|e| {
+        handlers.push(Handler::Fn(|e| {
             info!("clicked on {}", e.target);
             if let Some(view) = e.scene.get_view_mut(&"button2".into()) {
                 view.visible = true;
                 e.scene.dirty = true;
             }
-        });
+        }));
 
         // tap button 1
         assert_eq!(scene.dirty, false);
-        click_at(&mut scene, &handlers, Point::new(15, 15));
+        click_at(&mut scene, &handlers, Point::new(15, 15), &mut ());
         assert_eq!(was_button_clicked(&mut scene, &"button1".into()), true);
         // confirm dirty
         assert_eq!(scene.dirty, true);
@@ -508,9 +1159,34 @@ mod tests {
         scene.focused = Some("textbox1".into());
 
         // send keyboard event
-        event_at_focused(&mut scene, &EventType::Keyboard(b'X'));
+        event_at_focused(&mut scene, &EventType::Key(KeyEvent::text('X')), &mut ());
         // confirm text is updated
         assert_eq!(get_view_title(&scene, ViewId::new("textbox1")), "fooX");
+
+        // non-ascii text makes it through
+        event_at_focused(&mut scene, &EventType::Key(KeyEvent::text('é')), &mut ());
+        assert_eq!(get_view_title(&scene, ViewId::new("textbox1")), "fooXé");
+
+        // releases and shortcut chords don't type anything
+        let mut release = KeyEvent::text('y');
+        release.state = KeyState::Released;
+        event_at_focused(&mut scene, &EventType::Key(release), &mut ());
+        let chord = KeyEvent::text('s').with_modifiers(Modifiers::ctrl());
+        event_at_focused(&mut scene, &EventType::Key(chord), &mut ());
+        assert_eq!(get_view_title(&scene, ViewId::new("textbox1")), "fooXé");
+    }
+
+    #[test]
+    fn test_key_event_text() {
+        let upper = KeyEvent::text('A');
+        assert_eq!(upper.key, Key::Char('a'));
+        assert_eq!(upper.text, Some('A'));
+        assert!(upper.modifiers.shift);
+        let lower = KeyEvent::text('a');
+        assert_eq!(lower.key, Key::Char('a'));
+        assert_eq!(lower.modifiers, Modifiers::NONE);
+        assert_eq!(KeyEvent::pressed(Key::Home).typed_text(), None);
+        assert!(!KeyEvent::released(Key::Escape).is_pressed());
     }
 
     #[test]
@@ -521,13 +1197,13 @@ mod tests {
             title: "view".into(),
             bounds: Bounds::new(0, 0, 10, 10),
             visible: true,
-            draw: Some(|e| {
+            draw: Some(Handler::Fn(|e| {
                 let mut color = &e.theme.fg;
                 if e.focused.is_some() && e.view.name.eq(e.focused.as_ref().unwrap()) {
                     color = &e.theme.bg;
                 }
                 e.ctx.fill_rect(&e.view.bounds, color);
-            }),
+            })),
             state: None,
             input: None,
             layout: None,
@@ -555,7 +1231,7 @@ mod tests {
         assert_eq!(scene.dirty, false);
         assert_eq!(scene.dirty_rect.is_empty(), true);
         // send tap to button
-        click_at(&mut scene, &vec![], Point::new(30, 30));
+        click_at(&mut scene, &vec![], Point::new(30, 30), &mut ());
         // check that dirty area is just for the button
         assert_eq!(scene.dirty, true);
         assert_eq!(
@@ -595,7 +1271,7 @@ mod tests {
         // nothing should be focused yet
         assert!(scene.focused.is_none());
 
-        click_at(&mut scene, &vec![], Point::new(45, 45));
+        click_at(&mut scene, &vec![], Point::new(45, 45), &mut ());
         scene.dump();
         // now the button should be focused
         assert!(scene.focused.is_some());
@@ -1,7 +1,7 @@
-use crate::geom::Bounds;
+use crate::geom::{Bounds, Size};
 use crate::gfx::draw_centered_text;
 use crate::view::{View, ViewId};
-use crate::{Action, DrawEvent, EventType, GuiEvent, KeyboardAction, LayoutEvent};
+use crate::{Action, DrawEvent, EventType, GuiEvent, Handler, Key, MeasureEvent};
 use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
@@ -9,14 +9,14 @@ use core::any::Any;
 use core::option::Option::Some;
 use log::info;
 
-pub fn make_list_view(name: &ViewId, data: Vec<&str>, selected: usize) -> View {
+pub fn make_list_view<C>(name: &ViewId, data: Vec<&str>, selected: usize) -> View<C> {
     View {
         name: name.clone(),
         title: name.as_str().into(),
         state: Some(ListState::new_with(data, selected)),
-        input: Some(input_list),
-        layout: Some(layout_list),
-        draw: Some(draw_list),
+        input: Some(Handler::Fn(input_list)),
+        measure: Some(Handler::Fn(measure_list)),
+        draw: Some(Handler::Fn(draw_list)),
         ..Default::default()
     }
 }
@@ -48,7 +48,7 @@ impl ListState {
     }
 }
 
-fn input_list(e: &mut GuiEvent) -> Option<Action> {
+fn input_list<C>(e: &mut GuiEvent<C>) -> Option<Action> {
     match &e.event_type {
         EventType::Tap(pt) => {
             e.scene.mark_dirty_view(e.target);
@@ -61,7 +61,7 @@ fn input_list(e: &mut GuiEvent) -> Option<Action> {
                     let n = y / cell_height;
                     if n >= 0 && n < state.items.len() as i32 {
                         state.selected = n as usize;
-                        return Some(Action::Command(state.items[state.selected].clone()));
+                        return Some(Action::Selected(state.selected));
                     }
                 }
             }
@@ -77,17 +77,17 @@ fn input_list(e: &mut GuiEvent) -> Option<Action> {
                 }
             }
         }
-        EventType::KeyboardAction(action) => {
+        EventType::Key(key) if key.is_pressed() => {
             e.scene.mark_dirty_view(e.target);
             if let Some(state) = e.scene.get_view_state::<ListState>(e.target) {
-                match action {
-                    KeyboardAction::Up => state.select_prev(),
-                    KeyboardAction::Down => state.select_next(),
-                    KeyboardAction::Return => {
+                match key.key {
+                    Key::Up => state.select_prev(),
+                    Key::Down => state.select_next(),
+                    Key::Home => state.selected = 0,
+                    Key::End => state.selected = state.items.len().saturating_sub(1),
+                    Key::Return => {
                         info!("firmly selecting the item");
-                        return Some(Action::Command(
-                            state.items[state.selected as usize].clone(),
-                        ));
+                        return Some(Action::Selected(state.selected));
                     }
                     _ => {}
                 }
@@ -98,7 +98,7 @@ fn input_list(e: &mut GuiEvent) -> Option<Action> {
     None
 }
 
-fn draw_list(e: &mut DrawEvent) {
+fn draw_list<C>(e: &mut DrawEvent<C>) {
     let bounds = e.view.bounds;
     e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
     let name = e.view.name.clone();
@@ -133,16 +133,20 @@ fn draw_list(e: &mut DrawEvent) {
     e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
 }
 
-fn layout_list(e: &mut LayoutEvent) {
-    if let Some(state) = e.scene.get_view_state::<ListState>(e.target) {
+fn measure_list<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return e.space;
+    };
+    let mut size = view.bounds.size;
+    if let Some(state) = view.get_state_ref::<ListState>() {
         let ch = e.theme.font.character_size;
-        let height = state.items.len() as u32 * ch.height * 2;
-        if let Some(view) = e.scene.get_view_mut(e.target) {
-            view.bounds.size.h = height as i32;
-        }
+        size.h = (state.items.len() as u32 * ch.height * 2) as i32;
     }
+    size
 }
+#[cfg(test)]
 mod tests {
+    use crate::Action;
     use crate::geom::{Bounds, Point};
     use crate::list_view::{ListState, make_list_view};
     use crate::scene::{Scene, click_at, draw_scene, layout_scene};
@@ -168,7 +172,8 @@ mod tests {
             assert_eq!(state.selected, 0);
         }
 
-        click_at(&mut scene, &vec![], Point::new(50, 30));
+        let res = click_at(&mut scene, &vec![], Point::new(50, 30), &mut ());
+        assert!(matches!(res, Some((_, Action::Selected(1)))));
 
         {
             let state = &mut scene
@@ -0,0 +1,403 @@
+use crate::accelerator::{AcceleratorPriority, AcceleratorTarget, KeyCombo};
+use crate::animation::{AnimationTarget, Easing};
+use crate::geom::{Bounds, Point};
+use crate::layouts::measure_std_panel;
+use crate::scene::Scene;
+use crate::timer::TimerId;
+use crate::view::Flex::Resize;
+use crate::view::{View, ViewId};
+use crate::{Action, EventType, GuiEvent, Handler, LayoutEvent};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use log::warn;
+
+pub type ScreenFn<C = ()> = fn(scene: &mut Scene<C>, screen: &ViewId);
+
+/// A screen is a view subtree that has been added to the scene without a parent. Only the
+/// screen on top of the navigator's stack is attached, so the rest stay out of layout,
+/// drawing and picking.
+#[derive(Debug)]
+pub struct Screen<C = ()> {
+    pub id: ViewId,
+    pub on_enter: Option<ScreenFn<C>>,
+    pub on_exit: Option<ScreenFn<C>>,
+}
+
+impl<C> Clone for Screen<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for Screen<C> {}
+
+impl<C> Screen<C> {
+    pub fn new(id: &ViewId) -> Screen<C> {
+        Screen {
+            id: *id,
+            on_enter: None,
+            on_exit: None,
+        }
+    }
+    pub fn with_on_enter(mut self, on_enter: ScreenFn<C>) -> Screen<C> {
+        self.on_enter = Some(on_enter);
+        self
+    }
+    pub fn with_on_exit(mut self, on_exit: ScreenFn<C>) -> Screen<C> {
+        self.on_exit = Some(on_exit);
+        self
+    }
+}
+
+/// How screens change. There is no fade because views can't be drawn transparently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    None,
+    /// Pushed screens slide in from the right and popped screens slide back out.
+    Slide(u64),
+}
+
+#[derive(Debug)]
+pub struct NavigatorState<C = ()> {
+    stack: Vec<Screen<C>>,
+    pub transition: Transition,
+    /// The screen sliding out, kept attached until the transition timer fires.
+    leaving: Option<(ViewId, TimerId)>,
+    /// The combo from `bind_back_key`. Other keys sent to the navigator are ignored.
+    back_key: Option<KeyCombo>,
+}
+
+impl<C> NavigatorState<C> {
+    pub fn current(&self) -> Option<&ViewId> {
+        self.stack.last().map(|screen| &screen.id)
+    }
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+}
+
+pub fn make_navigator<C: 'static>(name: &ViewId, transition: Transition) -> View<C> {
+    View {
+        name: *name,
+        h_flex: Resize,
+        v_flex: Resize,
+        state: Some(Box::new(NavigatorState::<C> {
+            stack: Vec::new(),
+            transition,
+            leaving: None,
+            back_key: None,
+        })),
+        input: Some(Handler::Fn(input_navigator)),
+        layout: Some(Handler::Fn(layout_navigator)),
+        measure: Some(Handler::Fn(measure_std_panel)),
+        draw: Some(Handler::Fn(|e| {
+            e.ctx.fill_rect(&e.view.bounds, &e.theme.panel_bg);
+        })),
+        ..Default::default()
+    }
+}
+
+/// Make the key go back a screen when the focused view doesn't use it.
+pub fn bind_back_key<C: 'static>(scene: &mut Scene<C>, navigator: &ViewId, combo: KeyCombo) {
+    let Some(state) = scene.get_view_state::<NavigatorState<C>>(navigator) else {
+        warn!("{navigator} is not a navigator");
+        return;
+    };
+    state.back_key = Some(combo);
+    scene.add_accelerator(
+        combo,
+        AcceleratorTarget::Forward(*navigator),
+        AcceleratorPriority::AfterFocused,
+    );
+}
+
+pub fn push_screen<C: 'static>(scene: &mut Scene<C>, navigator: &ViewId, screen: Screen<C>) {
+    let Some(state) = scene.get_view_state::<NavigatorState<C>>(navigator) else {
+        warn!("{navigator} is not a navigator");
+        return;
+    };
+    let from = state.stack.last().copied();
+    state.stack.push(screen);
+    switch_screens(scene, navigator, from, Some(screen), true);
+}
+
+/// Remove the top screen and show the one below it. Returns the removed screen, which
+/// stays in the scene so it can be pushed again.
+pub fn pop_screen<C: 'static>(scene: &mut Scene<C>, navigator: &ViewId) -> Option<ViewId> {
+    let state = scene.get_view_state::<NavigatorState<C>>(navigator)?;
+    let from = state.stack.pop()?;
+    let to = state.stack.last().copied();
+    switch_screens(scene, navigator, Some(from), to, false);
+    Some(from.id)
+}
+
+/// Swap the top screen for a new one. Returns the replaced screen.
+pub fn replace_screen<C: 'static>(
+    scene: &mut Scene<C>,
+    navigator: &ViewId,
+    screen: Screen<C>,
+) -> Option<ViewId> {
+    let state = scene.get_view_state::<NavigatorState<C>>(navigator)?;
+    let from = state.stack.pop();
+    state.stack.push(screen);
+    switch_screens(scene, navigator, from, Some(screen), true);
+    from.map(|from| from.id)
+}
+
+/// Pop the top screen unless it is the last one. Returns true if it went back.
+pub fn go_back<C: 'static>(scene: &mut Scene<C>, navigator: &ViewId) -> bool {
+    match scene.get_view_state::<NavigatorState<C>>(navigator) {
+        Some(state) if state.stack.len() > 1 => pop_screen(scene, navigator).is_some(),
+        _ => false,
+    }
+}
+
+fn switch_screens<C: 'static>(
+    scene: &mut Scene<C>,
+    navigator: &ViewId,
+    from: Option<Screen<C>>,
+    to: Option<Screen<C>>,
+    forward: bool,
+) {
+    finish_transition(scene, navigator);
+    let Some(bounds) = scene.get_view(navigator).map(|view| view.bounds) else {
+        return;
+    };
+    let full = Bounds::new_from(Point::zero(), bounds.size);
+    if let Some(from) = &from {
+        if let Some(on_exit) = from.on_exit {
+            on_exit(scene, &from.id);
+        }
+        if let Some(focused) = scene.get_focused()
+            && is_inside(scene, &focused, &from.id)
+        {
+            scene.clear_focused();
+        }
+    }
+    if let Some(to) = &to {
+        scene.move_view_to_parent(&to.id, navigator);
+        if let Some(view) = scene.get_view_mut(&to.id) {
+            view.visible = true;
+            view.bounds = full;
+        }
+    }
+    let transition = scene
+        .get_view_state::<NavigatorState<C>>(navigator)
+        .map(|state| state.transition)
+        .unwrap_or(Transition::None);
+    match (transition, from, to) {
+        (Transition::Slide(duration), Some(from), Some(to)) if from.id != to.id => {
+            let offset = Point::new(if forward { full.w() } else { -full.w() }, 0);
+            scene.animate(
+                &to.id,
+                AnimationTarget::Bounds(full + offset, full),
+                duration,
+                Easing::EaseOut,
+            );
+            scene.animate(
+                &from.id,
+                AnimationTarget::Bounds(full, full - offset),
+                duration,
+                Easing::EaseOut,
+            );
+            let timer = scene.start_timer(navigator, duration, false);
+            if let Some(state) = scene.get_view_state::<NavigatorState<C>>(navigator) {
+                state.leaving = Some((from.id, timer));
+            }
+        }
+        (_, Some(from), _) if to.is_none_or(|to| to.id != from.id) => {
+            scene.remove_view_from_parent(navigator, &from.id);
+        }
+        _ => {}
+    }
+    if let Some(to) = &to
+        && let Some(on_enter) = to.on_enter
+    {
+        on_enter(scene, &to.id);
+    }
+    scene.mark_view_layout_dirty(navigator);
+}
+
+fn finish_transition<C: 'static>(scene: &mut Scene<C>, navigator: &ViewId) {
+    let Some((leaving, timer)) = scene
+        .get_view_state::<NavigatorState<C>>(navigator)
+        .and_then(|state| state.leaving.take())
+    else {
+        return;
+    };
+    scene.cancel_timer(timer);
+    scene.mark_dirty_view(navigator);
+    let current = scene
+        .get_view_state::<NavigatorState<C>>(navigator)
+        .and_then(|state| state.current().copied());
+    if current != Some(leaving) {
+        scene.remove_view_from_parent(navigator, &leaving);
+    }
+}
+
+fn is_inside<C>(scene: &Scene<C>, view: &ViewId, ancestor: &ViewId) -> bool {
+    let mut current = view;
+    loop {
+        if current == ancestor {
+            return true;
+        }
+        match scene.get_parent_for_view(current) {
+            Some(parent) => current = parent,
+            None => return false,
+        }
+    }
+}
+
+fn input_navigator<C: 'static>(e: &mut GuiEvent<C>) -> Option<Action> {
+    match &e.event_type {
+        EventType::Timer(_) => {
+            finish_transition(e.scene, e.target);
+            None
+        }
+        EventType::Key(key) => {
+            let is_back = e
+                .scene
+                .get_view_state::<NavigatorState<C>>(e.target)
+                .and_then(|state| state.back_key)
+                .is_some_and(|combo| combo.matches(key));
+            if is_back && go_back(e.scene, e.target) {
+                Some(Action::Back)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn layout_navigator<C>(pass: &mut LayoutEvent<C>) {
+    if let Some(view) = pass.scene.get_view_mut(pass.target) {
+        if view.h_flex == Resize {
+            view.bounds.size.w = pass.space.w;
+        }
+        if view.v_flex == Resize {
+            view.bounds.size.h = pass.space.h;
+        }
+        let size = view.bounds.size;
+        for kid in pass.scene.get_children_ids(pass.target) {
+            pass.layout_child(&kid, size);
+            // sliding screens are positioned by their animation
+            if !pass.scene.is_animating(&kid)
+                && let Some(view) = pass.scene.get_view_mut(&kid)
+            {
+                view.bounds.position = Point::zero();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::accelerator::KeyCombo;
+    use crate::geom::{Bounds, Point};
+    use crate::navigator::{
+        NavigatorState, Screen, Transition, bind_back_key, make_navigator, pop_screen, push_screen,
+        replace_screen,
+    };
+    use crate::scene::{Scene, event_at_focused, layout_scene, pick_at};
+    use crate::test::MockDrawingContext;
+    use crate::view::{Flex, View, ViewId};
+    use crate::{Action, EventType, Key, KeyEvent};
+
+    fn make_screen(scene: &mut Scene, name: &'static str) -> ViewId {
+        let id = ViewId::new(name);
+        scene.add_view(View {
+            name: id,
+            h_flex: Flex::Resize,
+            v_flex: Flex::Resize,
+            ..Default::default()
+        });
+        id
+    }
+
+    fn current(scene: &mut Scene, nav: &ViewId) -> Option<ViewId> {
+        scene
+            .get_view_state::<NavigatorState>(nav)
+            .and_then(|state| state.current().copied())
+    }
+
+    #[test]
+    fn test_navigation_stack() {
+        let mut scene = Scene::new();
+        let theme = MockDrawingContext::make_mock_theme();
+        let nav: ViewId = "nav".into();
+        scene.add_view_to_root(make_navigator(&nav, Transition::None));
+        let home = make_screen(&mut scene, "home");
+        let settings = make_screen(&mut scene, "settings");
+        let about = make_screen(&mut scene, "about");
+
+        push_screen(
+            &mut scene,
+            &nav,
+            Screen::new(&home).with_on_exit(|scene, id| scene.hide_view(id)),
+        );
+        push_screen(&mut scene, &nav, Screen::new(&settings));
+        layout_scene(&mut scene, &theme);
+        assert_eq!(scene.get_children_ids(&nav), [settings]);
+        // on_exit ran and the inactive screen can't be picked
+        assert!(!scene.get_view(&home).unwrap().visible);
+        assert_eq!(
+            pick_at(&mut scene, &Point::new(5, 5)).last().unwrap().0,
+            settings
+        );
+
+        assert_eq!(
+            replace_screen(&mut scene, &nav, Screen::new(&about)),
+            Some(settings)
+        );
+        assert_eq!(current(&mut scene, &nav), Some(about));
+
+        // the back key pops, but never past the first screen
+        bind_back_key(&mut scene, &nav, KeyCombo::new(Key::Escape));
+        // other keys don't, even when the navigator itself is focused
+        scene.set_focused(&nav);
+        let letter = EventType::Key(KeyEvent::text('q'));
+        assert!(event_at_focused(&mut scene, &letter, &mut ()).is_none());
+        assert_eq!(current(&mut scene, &nav), Some(about));
+        let back = EventType::Key(KeyEvent::pressed(Key::Escape));
+        let res = event_at_focused(&mut scene, &back, &mut ());
+        assert!(matches!(res, Some((id, Action::Back)) if id == nav));
+        assert_eq!(current(&mut scene, &nav), Some(home));
+        assert!(scene.get_view(&home).unwrap().visible);
+        assert!(event_at_focused(&mut scene, &back, &mut ()).is_none());
+        assert_eq!(pop_screen(&mut scene, &nav), Some(home));
+        assert!(scene.get_children_ids(&nav).is_empty());
+    }
+
+    #[test]
+    fn test_slide_transition() {
+        let mut scene = Scene::new();
+        let theme = MockDrawingContext::make_mock_theme();
+        let nav: ViewId = "nav".into();
+        scene.add_view_to_root(make_navigator(&nav, Transition::Slide(100)));
+        let home = make_screen(&mut scene, "home");
+        let settings = make_screen(&mut scene, "settings");
+        push_screen(&mut scene, &nav, Screen::new(&home));
+        layout_scene(&mut scene, &theme);
+
+        push_screen(&mut scene, &nav, Screen::new(&settings));
+        layout_scene(&mut scene, &theme);
+        scene.tick(50, &mut ());
+        // both screens are attached while they slide
+        assert_eq!(scene.get_children_ids(&nav), [home, settings]);
+        assert_eq!(
+            scene.get_view_bounds(&settings),
+            Some(Bounds::new(50, 0, 200, 200))
+        );
+        assert_eq!(
+            scene.get_view_bounds(&home),
+            Some(Bounds::new(-150, 0, 200, 200))
+        );
+        scene.tick(100, &mut ());
+        assert_eq!(scene.get_children_ids(&nav), [settings]);
+        assert_eq!(
+            scene.get_view_bounds(&settings),
+            Some(Bounds::new(0, 0, 200, 200))
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::DrawEvent;
 
-pub fn draw_std_panel(e: &mut DrawEvent) {
+pub fn draw_std_panel<C>(e: &mut DrawEvent<C>) {
     let bounds = e.view.bounds;
     e.ctx.fill_rect(&bounds, &e.theme.bg);
     e.ctx.stroke_rect(&bounds, &e.theme.fg);
@@ -0,0 +1,331 @@
+use crate::geom::Point;
+use crate::input::{InputEvent, dispatch_input};
+use crate::scene::{EventResult, Scene};
+use crate::{Callback, Key, KeyEvent, KeyState, Modifiers};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+/// An input event and the scene time it was delivered at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    pub time_ms: u64,
+    pub event: InputEvent,
+}
+
+/// A log of the pointer, scroll and key events delivered to a scene, for reproducing bugs.
+///
+/// The text form has one event per line: the time, the kind and its arguments.
+/// ```text
+/// 1200 tap 40 52
+/// 1450 scroll 0 -3
+/// 1800 key char:104 d 104
+/// ```
+/// A key has the key name, the flags (`d` or `u` for down or up, then `S`, `C`, `A`, `M`
+/// for the modifiers and `R` for a repeat) and the code point of its text or `-`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+    pub events: Vec<RecordedEvent>,
+}
+
+/// The line of a recording that could not be read, counting from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+}
+
+impl Recording {
+    pub fn new() -> Recording {
+        Recording { events: Vec::new() }
+    }
+    pub fn push(&mut self, time_ms: u64, event: InputEvent) {
+        self.events.push(RecordedEvent { time_ms, event });
+    }
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for rec in &self.events {
+            let _ = write!(out, "{} ", rec.time_ms);
+            match &rec.event {
+                InputEvent::Tap(pt) => {
+                    let _ = write!(out, "tap {} {}", pt.x, pt.y);
+                }
+                InputEvent::Press(pt) => {
+                    let _ = write!(out, "press {} {}", pt.x, pt.y);
+                }
+                InputEvent::Hover(pt) => {
+                    let _ = write!(out, "hover {} {}", pt.x, pt.y);
+                }
+                InputEvent::Drag(pt) => {
+                    let _ = write!(out, "drag {} {}", pt.x, pt.y);
+                }
+                InputEvent::Scroll(x, y) => {
+                    let _ = write!(out, "scroll {} {}", x, y);
+                }
+                InputEvent::Key(key) => write_key(&mut out, key),
+            }
+            out.push('\n');
+        }
+        out
+    }
+    /// Read the text form. Blank lines and lines starting with `#` are skipped.
+    pub fn parse(text: &str) -> Result<Recording, ParseError> {
+        let mut recording = Recording::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = ParseError { line: i + 1 };
+            let mut parts = line.split_whitespace();
+            let time_ms = parts.next().and_then(|s| s.parse().ok()).ok_or(error)?;
+            let event = parse_event(&mut parts).ok_or(error)?;
+            if parts.next().is_some() {
+                return Err(error);
+            }
+            recording.push(time_ms, event);
+        }
+        Ok(recording)
+    }
+}
+
+const KEY_NAMES: [(Key, &str); 13] = [
+    (Key::Left, "left"),
+    (Key::Right, "right"),
+    (Key::Up, "up"),
+    (Key::Down, "down"),
+    (Key::Backspace, "backspace"),
+    (Key::Delete, "delete"),
+    (Key::Return, "return"),
+    (Key::Tab, "tab"),
+    (Key::Escape, "escape"),
+    (Key::Home, "home"),
+    (Key::End, "end"),
+    (Key::PageUp, "pageup"),
+    (Key::PageDown, "pagedown"),
+];
+
+fn write_key(out: &mut String, key: &KeyEvent) {
+    out.push_str("key ");
+    match key.key {
+        Key::Char(ch) => {
+            let _ = write!(out, "char:{}", ch as u32);
+        }
+        Key::F(n) => {
+            let _ = write!(out, "f{}", n);
+        }
+        Key::Unknown => out.push_str("unknown"),
+        other => {
+            if let Some((_, name)) = KEY_NAMES.iter().find(|(k, _)| *k == other) {
+                out.push_str(name);
+            }
+        }
+    }
+    out.push(' ');
+    out.push(if key.is_pressed() { 'd' } else { 'u' });
+    for (set, flag) in [
+        (key.modifiers.shift, 'S'),
+        (key.modifiers.ctrl, 'C'),
+        (key.modifiers.alt, 'A'),
+        (key.modifiers.meta, 'M'),
+        (key.repeat, 'R'),
+    ] {
+        if set {
+            out.push(flag);
+        }
+    }
+    match key.text {
+        Some(ch) => {
+            let _ = write!(out, " {}", ch as u32);
+        }
+        None => out.push_str(" -"),
+    }
+}
+
+fn parse_point<'a>(parts: &mut impl Iterator<Item = &'a str>) -> Option<Point> {
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    Some(Point::new(x, y))
+}
+
+fn parse_char(s: &str) -> Option<char> {
+    char::from_u32(s.parse().ok()?)
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    if let Some(code) = name.strip_prefix("char:") {
+        return parse_char(code).map(Key::Char);
+    }
+    if name == "unknown" {
+        return Some(Key::Unknown);
+    }
+    if let Some((key, _)) = KEY_NAMES.iter().find(|(_, n)| *n == name) {
+        return Some(*key);
+    }
+    name.strip_prefix('f')?.parse().ok().map(Key::F)
+}
+
+fn parse_event<'a>(parts: &mut impl Iterator<Item = &'a str>) -> Option<InputEvent> {
+    match parts.next()? {
+        "tap" => parse_point(parts).map(InputEvent::Tap),
+        "press" => parse_point(parts).map(InputEvent::Press),
+        "hover" => parse_point(parts).map(InputEvent::Hover),
+        "drag" => parse_point(parts).map(InputEvent::Drag),
+        "scroll" => {
+            let pt = parse_point(parts)?;
+            Some(InputEvent::Scroll(pt.x, pt.y))
+        }
+        "key" => {
+            let key = parse_key(parts.next()?)?;
+            let mut flags = parts.next()?.chars();
+            let state = match flags.next()? {
+                'd' => KeyState::Pressed,
+                'u' => KeyState::Released,
+                _ => return None,
+            };
+            let mut modifiers = Modifiers::NONE;
+            let mut repeat = false;
+            for flag in flags {
+                match flag {
+                    'S' => modifiers.shift = true,
+                    'C' => modifiers.ctrl = true,
+                    'A' => modifiers.alt = true,
+                    'M' => modifiers.meta = true,
+                    'R' => repeat = true,
+                    _ => return None,
+                }
+            }
+            let text = match parts.next()? {
+                "-" => None,
+                code => Some(parse_char(code)?),
+            };
+            Some(InputEvent::Key(KeyEvent {
+                key,
+                text,
+                modifiers,
+                state,
+                repeat,
+            }))
+        }
+        _ => None,
+    }
+}
+
+impl<C> Scene<C> {
+    /// Start logging the events passed to `press_at`, `drag_at`, `click_at` and
+    /// `event_at_focused`. They are stamped with the time of the last `tick`.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Recording::new());
+    }
+    pub fn stop_recording(&mut self) -> Option<Recording> {
+        self.recording.take()
+    }
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+    pub(crate) fn record(&mut self, event: InputEvent) {
+        let now = self.now_ms;
+        if let Some(recording) = &mut self.recording {
+            recording.push(now, event);
+        }
+    }
+}
+
+/// Feed a recording back into the scene without a display. The scene clock is ticked to
+/// the time of each event first, so timers and animations run in the same order as they
+/// did when it was recorded. Returns the actions from the ticks and the events.
+pub fn replay<C>(
+    scene: &mut Scene<C>,
+    handlers: &Vec<Callback<C>>,
+    recording: &Recording,
+    context: &mut C,
+) -> Vec<EventResult> {
+    let mut results = Vec::new();
+    for rec in &recording.events {
+        results.extend(scene.tick(rec.time_ms, context));
+        if let Some(result) = dispatch_input(scene, handlers, rec.event.clone(), context) {
+            results.push(result);
+        }
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Action;
+    use crate::button::make_button;
+    use crate::geom::Point;
+    use crate::input::InputEvent;
+    use crate::recorder::{ParseError, Recording, replay};
+    use crate::scene::{Scene, click_at, event_at_focused};
+    use crate::text_input::make_text_input;
+    use crate::view::ViewId;
+    use crate::{EventType, Key, KeyEvent, Modifiers};
+    use alloc::vec;
+
+    fn make_form() -> Scene {
+        let mut scene = Scene::new();
+        scene.add_view_to_root(make_button(&"ok".into(), "OK").position_at(10, 10));
+        scene.add_view_to_root(make_text_input("name", "").position_at(10, 50));
+        scene
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut scene = make_form();
+        scene.start_recording();
+        scene.tick(100, &mut ());
+        click_at(&mut scene, &vec![], Point::new(60, 60), &mut ());
+        scene.tick(250, &mut ());
+        event_at_focused(&mut scene, &EventType::Key(KeyEvent::text('H')), &mut ());
+        event_at_focused(&mut scene, &EventType::Key(KeyEvent::text(' ')), &mut ());
+        let chord = KeyEvent::pressed(Key::F(5)).with_modifiers(Modifiers::ctrl());
+        event_at_focused(&mut scene, &EventType::Key(chord.clone()), &mut ());
+        scene.tick(400, &mut ());
+        click_at(&mut scene, &vec![], Point::new(15, 15), &mut ());
+        let recording = scene.stop_recording().unwrap();
+        assert_eq!(recording.events.len(), 5);
+        assert_eq!(
+            recording.events[0].event,
+            InputEvent::Tap(Point::new(60, 60))
+        );
+        assert_eq!(recording.events[3].time_ms, 250);
+
+        let text = recording.to_text();
+        assert!(text.starts_with("100 tap 60 60\n250 key char:104 dS 72\n"));
+        assert!(text.contains("250 key f5 dC -\n"));
+        assert_eq!(Recording::parse(&text), Ok(recording.clone()));
+
+        // replaying into a fresh scene gives the same actions
+        let mut scene = make_form();
+        let results = replay(&mut scene, &vec![], &recording, &mut ());
+        let name: ViewId = "name".into();
+        let ok: ViewId = "ok".into();
+        assert!(
+            matches!(&results[0], (id, Action::TextChanged(text)) if id == &name && text == "H")
+        );
+        assert!(
+            matches!(&results[1], (id, Action::TextChanged(text)) if id == &name && text == "H ")
+        );
+        assert!(matches!(results.last(), Some((id, Action::Generic)) if id == &ok));
+    }
+
+    #[test]
+    fn test_load_log() {
+        let log = "# tap the button twice\n\n1000 press 12 12\n1010 tap 12 12\n2000 tap 12 12\n";
+        let recording = Recording::parse(log).unwrap();
+        assert_eq!(recording.events.len(), 3);
+        let mut scene = make_form();
+        let results = replay(&mut scene, &vec![], &recording, &mut ());
+        assert_eq!(results.len(), 2);
+        assert_eq!(scene.now_ms(), 2000);
+
+        assert_eq!(
+            Recording::parse("10 tap 1 2\n20 tap 1\n"),
+            Err(ParseError { line: 2 })
+        );
+        assert_eq!(
+            Recording::parse("10 key bogus d -"),
+            Err(ParseError { line: 1 })
+        );
+    }
+}
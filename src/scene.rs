@@ -1,15 +1,28 @@
-use crate::geom::{Bounds, Point};
+use crate::accelerator::{AcceleratorPriority, AcceleratorScope, AcceleratorTarget};
+use crate::animation::Animation;
+use crate::geom::{Bounds, Point, Size};
 use crate::gfx::DrawingContext;
-use crate::view::{View, ViewId};
-use crate::{Action, Callback, DrawEvent, EventType, GuiEvent, LayoutEvent, LayoutFn, Theme};
+use crate::input::InputEvent;
+use crate::recorder::Recording;
+use crate::scroll_view::scroll_enclosing;
+use crate::timer::Timer;
+use crate::view::Flex::Intrinsic;
+use crate::view::{Direction, View, ViewId};
+use crate::{
+    Action, Callback, DrawEvent, EventType, GuiEvent, Handler, KeyEvent, LayoutEvent,
+    LayoutHandler, Lifecycle, LifecycleEvent, Theme,
+};
 use alloc::vec::Vec;
 use alloc::{format, vec};
 use hashbrown::HashMap;
 use log::{info, warn};
 
+/// The views of a UI and the state of its input, focus and layout. `C` is the type of the
+/// app context passed to the functions that dispatch events, such as `click_at`, and from
+/// there to the input handlers. Use `()` for no context.
 #[derive(Debug)]
-pub struct Scene {
-    pub(crate) keys: HashMap<ViewId, View>,
+pub struct Scene<C = ()> {
+    pub(crate) keys: HashMap<ViewId, View<C>>,
     children: HashMap<ViewId, Vec<ViewId>>,
     parents: HashMap<ViewId, ViewId>,
     pub(crate) dirty: bool,
@@ -17,10 +30,37 @@ pub struct Scene {
     pub dirty_rect: Bounds,
     pub root_id: ViewId,
     pub(crate) focused: Option<ViewId>,
+    /// The view under the pointer while it is down.
+    pub(crate) pressed: Option<ViewId>,
+    /// The view under the mouse pointer.
+    pub(crate) hovered: Option<ViewId>,
     pub layout_dirty: bool,
+    /// The views to lay out again on the next pass, without a full layout.
+    pending_layouts: Vec<ViewId>,
+    /// The space each view was given by its parent in the last layout.
+    pub(crate) layout_spaces: HashMap<ViewId, Size>,
+    pub(crate) accelerators: Vec<AcceleratorScope>,
+    pub(crate) timers: Vec<Timer>,
+    pub(crate) next_timer_id: u32,
+    pub(crate) now_ms: u64,
+    pub(crate) animations: Vec<Animation<C>>,
+    pub(crate) next_animation_id: u32,
+    pub(crate) recording: Option<Recording>,
+    pub(crate) drag: Option<PointerDrag>,
 }
 
-impl Scene {
+/// Tracks the pointer from `press_at` until it is released.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PointerDrag {
+    start: Point,
+    last: Point,
+    moved: bool,
+}
+
+/// How far the pointer has to move while down before it is a drag rather than a tap.
+const DRAG_SLOP: i32 = 4;
+
+impl<C> Scene<C> {
     pub fn dump(&self) {
         info!("scene");
         info!(
@@ -44,17 +84,39 @@ impl Scene {
     }
 }
 
-impl Scene {
+impl<C> Scene<C> {
     pub fn root_id(&self) -> ViewId {
         self.root_id
     }
     pub fn set_focused(&mut self, name: &ViewId) {
-        if self.focused.is_some() {
-            let fo = self.focused.as_ref().unwrap().clone();
-            self.mark_dirty_view(&fo);
+        if self.is_focused(name) {
+            return;
         }
+        self.clear_focused();
         self.focused = Some(name.clone());
         self.mark_dirty_view(name);
+        self.fire_lifecycle(name, Lifecycle::Focused);
+    }
+    pub fn clear_focused(&mut self) {
+        if let Some(fo) = self.focused.take() {
+            self.mark_dirty_view(&fo);
+            self.fire_lifecycle(&fo, Lifecycle::Unfocused);
+        }
+    }
+    pub fn get_pressed(&self) -> Option<ViewId> {
+        self.pressed
+    }
+    pub fn get_hovered(&self) -> Option<ViewId> {
+        self.hovered
+    }
+    fn set_pressed(&mut self, pressed: Option<ViewId>) {
+        if let Some(old) = self.pressed {
+            self.mark_dirty_view(&old);
+        }
+        self.pressed = pressed;
+        if let Some(new) = pressed {
+            self.mark_dirty_view(&new);
+        }
     }
     pub fn get_focused(&self) -> Option<ViewId> {
         self.focused.clone()
@@ -69,17 +131,127 @@ impl Scene {
             false
         }
     }
-    pub fn show_view(&mut self, name: &ViewId) {
+    /// True if the view and all of its ancestors are enabled.
+    pub fn is_enabled(&self, name: &ViewId) -> bool {
+        let mut current = name;
+        loop {
+            match self.get_view(current) {
+                Some(view) if view.enabled => {}
+                _ => return false,
+            }
+            match self.parents.get(current) {
+                Some(parent) => current = parent,
+                None => return true,
+            }
+        }
+    }
+    /// The direction of the view, from the nearest ancestor that sets one. Left to right if
+    /// none do.
+    pub fn get_direction(&self, name: &ViewId) -> Direction {
+        let mut current = name;
+        loop {
+            if let Some(direction) = self.get_view(current).and_then(|view| view.direction) {
+                return direction;
+            }
+            match self.parents.get(current) {
+                Some(parent) => current = parent,
+                None => return Direction::Ltr,
+            }
+        }
+    }
+    /// Set the direction of the whole scene. Views can still set their own for their subtree.
+    pub fn set_direction(&mut self, direction: Direction) {
+        let root = self.root_id;
+        if let Some(view) = self.get_view_mut(&root) {
+            view.direction = Some(direction);
+        }
+        // text alignment flips even where nothing moves
+        self.mark_layout_dirty();
+        self.mark_dirty_all();
+    }
+    pub fn set_enabled(&mut self, name: &ViewId, enabled: bool) {
         if let Some(view) = self.get_view_mut(name) {
-            view.visible = true;
+            view.enabled = enabled;
+        }
+        if let Some(focused) = self.focused
+            && !self.is_enabled(&focused)
+        {
+            self.clear_focused();
         }
         self.mark_dirty_view(name);
     }
+    /// Move focus to the next visible and enabled view that handles input, in tree order.
+    pub fn focus_next(&mut self) -> Option<ViewId> {
+        self.move_focus(1)
+    }
+    pub fn focus_prev(&mut self) -> Option<ViewId> {
+        self.move_focus(-1)
+    }
+    fn move_focus(&mut self, step: isize) -> Option<ViewId> {
+        let mut candidates = vec![];
+        self.collect_focusable(&self.root_id.clone(), &mut candidates);
+        if candidates.is_empty() {
+            return None;
+        }
+        let len = candidates.len() as isize;
+        let next = match self
+            .focused
+            .and_then(|focused| candidates.iter().position(|id| id == &focused))
+        {
+            Some(n) => (n as isize + step).rem_euclid(len),
+            None if step > 0 => 0,
+            None => len - 1,
+        };
+        let next = candidates[next as usize];
+        self.set_focused(&next);
+        self.scroll_into_view(&next);
+        Some(next)
+    }
+    fn collect_focusable(&self, name: &ViewId, coll: &mut Vec<ViewId>) {
+        if let Some(view) = self.get_view(name)
+            && view.visible
+            && view.enabled
+        {
+            if view.input.is_some() {
+                coll.push(*name);
+            }
+            for kid in self.get_children_ids(name) {
+                self.collect_focusable(&kid, coll);
+            }
+        }
+    }
+    pub fn show_view(&mut self, name: &ViewId) {
+        self.set_visible(name, true);
+    }
     pub fn hide_view(&mut self, name: &ViewId) {
+        self.set_visible(name, false);
+    }
+    fn set_visible(&mut self, name: &ViewId, visible: bool) {
+        let mut changed = false;
         if let Some(view) = self.get_view_mut(name) {
-            view.visible = false;
+            changed = view.visible != visible;
+            view.visible = visible;
+        }
+        if changed {
+            self.mark_view_layout_dirty(name);
+            let lifecycle = if visible {
+                Lifecycle::Shown
+            } else {
+                Lifecycle::Hidden
+            };
+            self.fire_lifecycle(name, lifecycle);
         }
-        self.mark_dirty_view(name);
+    }
+    fn fire_lifecycle(&mut self, name: &ViewId, lifecycle: Lifecycle) {
+        let Some(handler) = self.get_view(name).and_then(|view| view.lifecycle.clone()) else {
+            return;
+        };
+        let mut event = LifecycleEvent {
+            scene: self,
+            target: name,
+            lifecycle,
+        };
+        handler.call(&mut event);
     }
     pub fn mark_dirty_all(&mut self) {
         self.dirty_rect = self.bounds;
@@ -92,9 +264,26 @@ impl Scene {
             self.dirty = true;
         }
     }
+    /// Lay out the whole scene again on the next `layout_scene`. Only the views that end up
+    /// moving or changing size are repainted.
     pub fn mark_layout_dirty(&mut self) {
         self.layout_dirty = true;
-        self.mark_dirty_all();
+    }
+    /// Lay out just this view again on the next `layout_scene`, because its content changed
+    /// in a way that can change its size, such as new text. Ancestors are included while
+    /// their size depends on their children.
+    pub fn mark_view_layout_dirty(&mut self, name: &ViewId) {
+        let mut target = *name;
+        while let Some(view) = self.get_view(&target)
+            && (view.h_flex == Intrinsic || view.v_flex == Intrinsic)
+            && let Some(parent) = self.parents.get(&target)
+        {
+            target = *parent;
+        }
+        self.mark_dirty_view(name);
+        if !self.pending_layouts.contains(&target) {
+            self.pending_layouts.push(target);
+        }
     }
 
     pub fn get_children_ids(&self, name: &ViewId) -> Vec<ViewId> {
@@ -104,7 +293,7 @@ impl Scene {
             Vec::new()
         }
     }
-    pub fn get_children_ids_filtered(&self, id: &ViewId, cb: fn(&View) -> bool) -> Vec<ViewId> {
+    pub fn get_children_ids_filtered(&self, id: &ViewId, cb: fn(&View<C>) -> bool) -> Vec<ViewId> {
         self.get_children_ids(id)
             .iter()
             .map(|kid| self.get_view(kid))
@@ -115,13 +304,21 @@ impl Scene {
             .collect()
     }
 
+    /// Change the text of a view, such as a label, and lay it out again to fit.
+    pub fn set_title(&mut self, name: &ViewId, title: &str) {
+        if let Some(view) = self.get_view_mut(name) {
+            view.title = title.into();
+        }
+        self.mark_view_layout_dirty(name);
+    }
+
     pub(crate) fn has_view(&self, name: &ViewId) -> bool {
         self.keys.contains_key(name)
     }
-    pub fn get_view(&self, name: &ViewId) -> Option<&View> {
+    pub fn get_view(&self, name: &ViewId) -> Option<&View<C>> {
         self.keys.get(name)
     }
-    pub fn get_view_mut(&mut self, name: &ViewId) -> Option<&mut View> {
+    pub fn get_view_mut(&mut self, name: &ViewId) -> Option<&mut View<C>> {
         self.keys.get_mut(name)
     }
     pub fn get_view_state<T: 'static>(&mut self, name: &ViewId) -> Option<&mut T> {
@@ -132,9 +329,9 @@ impl Scene {
         }
         None
     }
-    pub fn get_view_layout(&mut self, name: &ViewId) -> Option<LayoutFn> {
+    pub fn get_view_layout(&mut self, name: &ViewId) -> Option<LayoutHandler<C>> {
         if let Some(view) = self.get_view_mut(name) {
-            return view.layout;
+            return view.layout.clone();
         }
         None
     }
@@ -147,14 +344,29 @@ impl Scene {
     pub(crate) fn viewcount(&self) -> usize {
         self.keys.len()
     }
-    pub fn remove_view(&mut self, name: &ViewId) -> Option<View> {
+    pub fn remove_view(&mut self, name: &ViewId) -> Option<View<C>> {
+        if self.is_focused(name) {
+            self.clear_focused();
+        }
+        self.fire_lifecycle(name, Lifecycle::Unmounted);
+        if self.pressed == Some(*name) {
+            self.pressed = None;
+        }
+        if self.hovered == Some(*name) {
+            self.hovered = None;
+        }
         self.mark_dirty_view(name);
+        self.cancel_timers_for_view(name);
+        self.pending_layouts.retain(|id| id != name);
+        self.layout_spaces.remove(name);
+        self.animations.retain(|anim| !anim.is_for(name));
         self.keys.remove(name)
     }
     pub fn get_parent_for_view(&self, name: &ViewId) -> Option<&ViewId> {
         self.parents.get(name)
     }
     pub fn remove_view_from_parent(&mut self, parent: &ViewId, child: &ViewId) {
+        self.mark_dirty_view(child);
         if let Some(children) = self.children.get_mut(parent) {
             if let Some(n) = children.iter().position(|name| name == child) {
                 children.remove(n);
@@ -165,8 +377,9 @@ impl Scene {
         } else {
             warn!("parent {parent} does not contain child {child}");
         }
+        self.mark_view_layout_dirty(parent);
     }
-    pub fn new_with_bounds(bounds: Bounds) -> Scene {
+    pub fn new_with_bounds(bounds: Bounds) -> Scene<C> {
         let root_id = ViewId::new("root");
         let root = View {
             name: root_id.clone(),
@@ -175,40 +388,55 @@ impl Scene {
             visible: true,
             input: None,
             state: None,
-            layout: Some(layout_root_panel),
-            draw: Some(|e| e.ctx.fill_rect(&e.view.bounds, &e.theme.panel_bg)),
+            layout: Some(Handler::Fn(layout_root_panel)),
+            draw: Some(Handler::Fn(|e| {
+                e.ctx.fill_rect(&e.view.bounds, &e.theme.panel_bg)
+            })),
             ..Default::default()
         };
-        let mut keys: HashMap<ViewId, View> = HashMap::new();
+        let mut keys: HashMap<ViewId, View<C>> = HashMap::new();
         keys.insert(root_id.clone(), root);
         Scene {
             bounds,
             keys,
             dirty: true,
             layout_dirty: true,
+            pending_layouts: vec![],
+            layout_spaces: HashMap::new(),
             root_id,
             focused: None,
+            pressed: None,
+            hovered: None,
             dirty_rect: bounds,
             children: HashMap::new(),
             parents: HashMap::new(),
+            accelerators: vec![AcceleratorScope::new(root_id, false)],
+            timers: vec![],
+            next_timer_id: 0,
+            now_ms: 0,
+            animations: vec![],
+            next_animation_id: 0,
+            recording: None,
+            drag: None,
         }
     }
-    pub fn new() -> Scene {
+    pub fn new() -> Scene<C> {
         let bounds = Bounds::new(0, 0, 200, 200);
         Self::new_with_bounds(bounds)
     }
-    pub fn add_view(&mut self, view: View) {
+    pub fn add_view(&mut self, view: View<C>) {
         let name = view.name.clone();
         if self.keys.contains_key(&name) {
             warn!("might be adding duplicate view key {name}");
         }
         self.keys.insert(name.clone(), view);
         self.mark_dirty_view(&name);
+        self.fire_lifecycle(&name, Lifecycle::Mounted);
     }
-    pub fn add_view_to_root(&mut self, view: View) {
+    pub fn add_view_to_root(&mut self, view: View<C>) {
         self.add_view_to_parent(view, &self.root_id.clone());
     }
-    pub fn add_view_to_parent(&mut self, view: View, parent: &ViewId) {
+    pub fn add_view_to_parent(&mut self, view: View<C>, parent: &ViewId) {
         if !self.children.contains_key(parent) {
             self.children.insert(parent.clone(), vec![]);
         }
@@ -217,14 +445,20 @@ impl Scene {
             children.push(view.name.clone());
         }
         self.add_view(view);
+        self.mark_view_layout_dirty(parent);
     }
     pub fn move_view_to_parent(&mut self, child: &ViewId, parent: &ViewId) {
+        if let Some(old_parent) = self.parents.get(child).copied() {
+            self.remove_view_from_parent(&old_parent, child);
+        }
         if !self.children.contains_key(parent) {
             self.children.insert(parent.clone(), vec![]);
         }
         if let Some(children) = self.children.get_mut(parent) {
             children.push(child.clone());
         }
+        self.parents.insert(*child, *parent);
+        self.mark_view_layout_dirty(parent);
     }
     pub fn remove_parent_and_children(&mut self, name: &ViewId) {
         let kids = self.get_children_ids(name);
@@ -235,20 +469,51 @@ impl Scene {
         self.remove_view(name);
     }
 
-    fn get_view_global_bounds(&self, view: &View) -> Bounds {
+    fn get_view_global_bounds(&self, view: &View<C>) -> Bounds {
         let mut current = &view.name;
         let mut offset = Point::zero();
         while let Some(parent) = self.parents.get(current) {
-            if let Some(bounds) = self.get_view_bounds(parent) {
-                offset = offset + bounds.position;
+            if let Some(view) = self.get_view(parent) {
+                offset = offset + view.bounds.position - view.scroll_offset;
             }
             current = parent;
         }
         view.bounds + offset
     }
+    fn is_ancestor(&self, ancestor: &ViewId, name: &ViewId) -> bool {
+        let mut current = name;
+        while let Some(parent) = self.parents.get(current) {
+            if parent == ancestor {
+                return true;
+            }
+            current = parent;
+        }
+        false
+    }
+    /// Repaint the old and new area of each view whose global bounds changed since `before`
+    /// was collected.
+    fn mark_moved(&mut self, before: Vec<(ViewId, Bounds)>) {
+        for (name, old) in before {
+            if let Some(view) = self.get_view(&name) {
+                let new = self.get_view_global_bounds(view);
+                if new != old {
+                    self.dirty_rect = self.dirty_rect.union(old).union(new);
+                    self.dirty = true;
+                }
+            }
+        }
+    }
+    fn collect_global_bounds(&self, name: &ViewId, coll: &mut Vec<(ViewId, Bounds)>) {
+        if let Some(view) = self.get_view(name) {
+            coll.push((*name, self.get_view_global_bounds(view)));
+        }
+        for kid in self.get_children_ids(name) {
+            self.collect_global_bounds(&kid, coll);
+        }
+    }
 }
 
-fn layout_root_panel(pass: &mut LayoutEvent) {
+fn layout_root_panel<C>(pass: &mut LayoutEvent<C>) {
     if let Some(view) = pass.scene.get_view_mut(&pass.target) {
         view.bounds.size.w = pass.space.w;
         view.bounds.size.h = pass.space.h;
@@ -260,22 +525,39 @@ fn layout_root_panel(pass: &mut LayoutEvent) {
 
 pub type EventResult = (ViewId, Action);
 
-pub fn click_at(scene: &mut Scene, handlers: &Vec<Callback>, pt: Point) -> Option<EventResult> {
+/// The pointer was released at `pt`. Taps the view under it, unless the pointer was
+/// dragged. The view's input handler and then `handlers` get `context`.
+pub fn click_at<C>(
+    scene: &mut Scene<C>,
+    handlers: &Vec<Callback<C>>,
+    pt: Point,
+    context: &mut C,
+) -> Option<EventResult> {
+    scene.record(InputEvent::Tap(pt));
+    scene.set_pressed(None);
+    // the pointer was dragged rather than tapped
+    if scene.drag.take().is_some_and(|drag| drag.moved) {
+        return None;
+    }
     let targets = pick_at(scene, &pt);
     if let Some((target, pt)) = targets.last() {
-        let mut event: GuiEvent = GuiEvent {
+        if !scene.is_enabled(target) {
+            return None;
+        }
+        let mut event: GuiEvent<C> = GuiEvent {
             scene,
             target,
             event_type: EventType::Tap(pt.clone()),
             action: None,
+            context,
         };
         if let Some(view) = event.scene.get_view(target) {
-            if let Some(input) = view.input {
-                event.action = input(&mut event);
+            if let Some(input) = view.input.clone() {
+                event.action = input.call(&mut event);
             }
         }
         for cb in handlers {
-            cb(&mut event);
+            cb.call(&mut event);
         }
         if let Some(action) = event.action {
             return Some((target.clone(), action));
@@ -284,39 +566,164 @@ pub fn click_at(scene: &mut Scene, handlers: &Vec<Callback>, pt: Point) -> Optio
     None
 }
 
-pub fn event_at_focused(scene: &mut Scene, event_type: &EventType) -> Option<EventResult> {
-    if scene.focused.is_some() {
-        let focused = scene.focused.as_ref().unwrap().clone();
-        let mut event: GuiEvent = GuiEvent {
-            scene,
-            target: &focused,
-            event_type: event_type.clone(),
-            action: None,
-        };
-        if let Some(view) = event.scene.get_view(&focused) {
-            if let Some(input) = view.input {
-                event.action = input(&mut event);
-            }
-            if let Some(action) = event.action {
-                return Some((focused, action));
-            }
+/// The pointer went down. Shows the view under it as pressed until `click_at` is called.
+pub fn press_at<C>(scene: &mut Scene<C>, pt: Point) {
+    scene.record(InputEvent::Press(pt));
+    scene.drag = Some(PointerDrag {
+        start: pt,
+        last: pt,
+        moved: false,
+    });
+    let target = pick_at(scene, &pt)
+        .last()
+        .map(|(target, _)| *target)
+        .filter(|target| scene.is_enabled(target));
+    scene.set_pressed(target);
+}
+
+/// The mouse moved. Tracks the view under it for hover effects, or drags if a button is down.
+pub fn hover_at<C>(scene: &mut Scene<C>, pt: Point) {
+    if scene.drag.is_some() {
+        drag_at(scene, pt);
+        return;
+    }
+    let target = pick_at(scene, &pt)
+        .last()
+        .map(|(target, _)| *target)
+        .filter(|target| scene.is_enabled(target));
+    if target != scene.hovered {
+        if let Some(old) = scene.hovered {
+            scene.mark_dirty_view(&old);
+        }
+        scene.hovered = target;
+        if let Some(new) = target {
+            scene.mark_dirty_view(&new);
+        }
+    }
+}
+
+/// The pointer moved while down. Once it has moved further than a tap would, the innermost
+/// scroll view under the press follows it and the release no longer taps.
+pub fn drag_at<C>(scene: &mut Scene<C>, pt: Point) {
+    let Some(mut drag) = scene.drag else {
+        return;
+    };
+    scene.record(InputEvent::Drag(pt));
+    if !drag.moved {
+        let dist = pt - drag.start;
+        if dist.x.abs() < DRAG_SLOP && dist.y.abs() < DRAG_SLOP {
+            return;
+        }
+        drag.moved = true;
+        scene.set_pressed(None);
+    }
+    let delta = pt - drag.last;
+    drag.last = pt;
+    scene.drag = Some(drag);
+    for (target, _) in pick_at(scene, &drag.start).iter().rev() {
+        if scene.is_enabled(target) && scene.scroll_by(target, delta.negate()) {
+            break;
         }
     }
+}
+
+/// Send an event to the focused view, with `context` for its input handler. Keys go to
+/// the accelerators too.
+pub fn event_at_focused<C>(
+    scene: &mut Scene<C>,
+    event_type: &EventType,
+    context: &mut C,
+) -> Option<EventResult> {
+    match event_type {
+        EventType::Scroll(x, y) => scene.record(InputEvent::Scroll(*x, *y)),
+        EventType::Key(key) => scene.record(InputEvent::Key(key.clone())),
+        _ => {}
+    }
+    if let EventType::Key(key) = event_type
+        && let Some(res) = fire_accelerator(scene, key, AcceleratorPriority::BeforeFocused, context)
+    {
+        return Some(res);
+    }
+    if let Some(focused) = scene.focused
+        && scene.is_enabled(&focused)
+        && let Some(action) = send_event(scene, &focused, event_type.clone(), context)
+    {
+        return Some((focused, action));
+    }
+    // scrolls the focused view doesn't use move the scroll views around it
+    if let EventType::Scroll(x, y) = event_type
+        && let Some(start) = scene.focused.or(scene.hovered)
+    {
+        return scroll_enclosing(scene, &start, *x, *y);
+    }
+    if let EventType::Key(key) = event_type {
+        return fire_accelerator(scene, key, AcceleratorPriority::AfterFocused, context);
+    }
     None
 }
 
+fn fire_accelerator<C>(
+    scene: &mut Scene<C>,
+    key: &KeyEvent,
+    priority: AcceleratorPriority,
+    context: &mut C,
+) -> Option<EventResult> {
+    let (owner, target) = scene.find_accelerator(key, priority)?;
+    match target {
+        AcceleratorTarget::View(target) | AcceleratorTarget::Forward(target)
+            if !scene.is_enabled(&target) =>
+        {
+            None
+        }
+        AcceleratorTarget::Action(action) => Some((owner, action)),
+        AcceleratorTarget::View(target) => {
+            let center = scene.get_view(&target)?.bounds.center();
+            send_event(scene, &target, EventType::Tap(center), context)
+                .map(|action| (target, action))
+        }
+        AcceleratorTarget::Forward(target) => {
+            send_event(scene, &target, EventType::Key(key.clone()), context)
+                .map(|action| (target, action))
+        }
+    }
+}
+
+pub(crate) fn send_event<C>(
+    scene: &mut Scene<C>,
+    target: &ViewId,
+    event_type: EventType,
+    context: &mut C,
+) -> Option<Action> {
+    let mut event: GuiEvent<C> = GuiEvent {
+        scene,
+        target,
+        event_type,
+        action: None,
+        context,
+    };
+    if let Some(view) = event.scene.get_view(target)
+        && let Some(input) = view.input.clone()
+    {
+        event.action = input.call(&mut event);
+    }
+    event.action
+}
+
 type Pick = (ViewId, Point);
 
-pub fn pick_at(scene: &mut Scene, pt: &Point) -> Vec<Pick> {
+pub fn pick_at<C>(scene: &mut Scene<C>, pt: &Point) -> Vec<Pick> {
     pick_at_view(scene, pt, &scene.root_id)
 }
 
-fn pick_at_view(scene: &Scene, pt: &Point, name: &ViewId) -> Vec<Pick> {
+fn pick_at_view<C>(scene: &Scene<C>, pt: &Point, name: &ViewId) -> Vec<Pick> {
     let mut coll: Vec<Pick> = vec![];
     if let Some(view) = scene.keys.get(name) {
         if view.bounds.contains(pt) && view.visible {
             coll.push((view.name.clone(), pt.clone()));
-            let pt2 = pt.subtract(&view.bounds.position);
+            if view.clip_children && !(view.bounds - view.padding).contains(pt) {
+                return coll;
+            }
+            let pt2 = pt.subtract(&view.bounds.position) + view.scroll_offset;
             for kid in scene.get_children_ids(&view.name) {
                 let mut coll2 = pick_at_view(scene, &pt2, &kid);
                 coll.append(&mut coll2);
@@ -326,48 +733,82 @@ fn pick_at_view(scene: &Scene, pt: &Point, name: &ViewId) -> Vec<Pick> {
     coll
 }
 
-pub fn draw_scene(scene: &mut Scene, ctx: &mut dyn DrawingContext, theme: &Theme) {
+pub fn draw_scene<C>(scene: &mut Scene<C>, ctx: &mut dyn DrawingContext, theme: &Theme) {
     if scene.dirty {
         ctx.fill_rect(&scene.bounds, &theme.panel_bg);
         let name = scene.root_id.clone();
-        draw_view(scene, ctx, theme, &name);
+        draw_view(scene, ctx, theme, &name, true, Direction::Ltr);
         scene.dirty = false;
         scene.dirty_rect = Bounds::new_empty();
     }
 }
 
-fn draw_view(scene: &mut Scene, ctx: &mut dyn DrawingContext, theme: &Theme, name: &ViewId) {
+fn draw_view<C>(
+    scene: &mut Scene<C>,
+    ctx: &mut dyn DrawingContext,
+    theme: &Theme,
+    name: &ViewId,
+    enabled: bool,
+    direction: Direction,
+) {
     let focused = &scene.focused.clone();
+    let pressed = &scene.pressed.clone();
+    let hovered = &scene.hovered.clone();
     let bounds = &scene.bounds.clone();
+    let Some(view) = scene.get_view(name) else {
+        return;
+    };
+    // only draw children if visible
+    if !view.visible {
+        return;
+    }
+    // a disabled view draws itself and its children with the disabled colors
+    let disabled;
+    let (theme, enabled) = if enabled && !view.enabled {
+        disabled = theme.disabled();
+        (&disabled, false)
+    } else {
+        (theme, enabled)
+    };
+    let direction = view.direction.unwrap_or(direction);
+    ctx.set_direction(direction);
     if let Some(view) = scene.get_view_mut(name)
-        && view.visible
+        && let Some(draw) = view.draw.clone()
     {
-        if let Some(draw) = view.draw {
-            let mut de: DrawEvent = DrawEvent {
-                theme,
-                view,
-                ctx,
-                focused,
-                bounds,
-            };
-            draw(&mut de);
-        }
+        let mut de: DrawEvent<C> = DrawEvent {
+            theme,
+            view,
+            ctx,
+            focused,
+            pressed,
+            hovered,
+            bounds,
+            enabled,
+            direction,
+        };
+        draw.call(&mut de);
     }
     if let Some(view) = scene.get_view(name) {
-        // only draw children if visible
-        if view.visible {
-            let bounds = view.bounds;
-            ctx.translate(&bounds.position);
-            for kid in scene.get_children_ids(&view.name) {
-                draw_view(scene, ctx, theme, &kid);
-            }
-            ctx.translate(&bounds.position.negate());
+        let clip = view
+            .clip_children
+            .then(|| ctx.clip_to(&(view.bounds - view.padding)));
+        let offset = view.bounds.position - view.scroll_offset;
+        ctx.translate(&offset);
+        for kid in scene.get_children_ids(&view.name) {
+            draw_view(scene, ctx, theme, &kid, enabled, direction);
+        }
+        ctx.translate(&offset.negate());
+        if let Some(clip) = clip {
+            ctx.restore_clip(clip);
         }
     }
 }
 
-pub fn layout_scene(scene: &mut Scene, theme: &Theme) {
+pub fn layout_scene<C>(scene: &mut Scene<C>, theme: &Theme) {
     if scene.layout_dirty {
+        let mut before = vec![];
+        scene.collect_global_bounds(&scene.root_id(), &mut before);
+        scene.layout_spaces.insert(scene.root_id, scene.bounds.size);
         let mut pass = LayoutEvent {
             target: &scene.root_id(),
             space: scene.bounds.size,
@@ -375,17 +816,62 @@ pub fn layout_scene(scene: &mut Scene, theme: &Theme) {
             theme,
         };
         if let Some(layout) = pass.scene.get_view_layout(&pass.scene.root_id()) {
-            layout(&mut pass);
+            layout.call(&mut pass);
         }
         scene.layout_dirty = false;
+        scene.pending_layouts.clear();
+        scene.mark_moved(before);
+    }
+    let pending = core::mem::take(&mut scene.pending_layouts);
+    for target in &pending {
+        // laying out an ancestor covers this view too
+        if !pending.iter().any(|other| scene.is_ancestor(other, target)) {
+            relayout_view(scene, theme, target);
+        }
     }
 }
 
+/// Lay out a subtree again in the space it was given last time, and repaint only the views
+/// that moved or changed size.
+fn relayout_view<C>(scene: &mut Scene<C>, theme: &Theme, target: &ViewId) {
+    // a view added since the last pass hasn't been given any space yet
+    let mut target = *target;
+    let space = loop {
+        if let Some(space) = scene.layout_spaces.get(&target) {
+            break *space;
+        }
+        match scene.parents.get(&target) {
+            Some(parent) => target = *parent,
+            None => return,
+        }
+    };
+    let mut before = vec![];
+    scene.collect_global_bounds(&target, &mut before);
+    let mut pass = LayoutEvent {
+        target: &target,
+        space,
+        scene,
+        theme,
+    };
+    pass.layout_child(&target, space);
+    scene.mark_moved(before);
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::button::make_button;
     use crate::geom::Bounds;
-    use crate::scene::Scene;
-    use crate::view::ViewId;
+    use crate::label::make_label;
+    use crate::layouts::layout_hbox;
+    use crate::scene::{Scene, layout_scene};
+    use crate::test::MockDrawingContext;
+    use crate::view::Flex::Resize;
+    use crate::view::{View, ViewId};
+    use crate::{Handler, LayoutHandler, Lifecycle, LifecycleHandler};
+    use alloc::rc::Rc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::cell::RefCell;
 
     #[test]
     fn basic_add_remove() {
@@ -422,6 +908,7 @@ mod tests {
 
         scene.move_view_to_parent(&child_id, &parent_id);
         assert_eq!(scene.get_children_ids(&parent_id).len(), 1);
+        assert_eq!(scene.get_parent_for_view(&child_id).unwrap(), &parent_id);
         let child2 = crate::tests::make_simple_view(&"child2".into());
         scene.add_view_to_parent(child2, &parent_id);
         assert_eq!(scene.get_children_ids(&parent_id).len(), 2);
@@ -431,4 +918,117 @@ mod tests {
         assert_eq!(scene.get_children_ids(&parent_id).len(), 0);
         assert_eq!(scene.viewcount(), 1);
     }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut scene: Scene = Scene::new();
+        let log: Rc<RefCell<Vec<Lifecycle>>> = Rc::new(RefCell::new(vec![]));
+        let events = log.clone();
+        let tab: ViewId = "tab".into();
+        let content: ViewId = "content".into();
+        scene.add_view_to_root(View {
+            name: tab,
+            visible: false,
+            lifecycle: Some(LifecycleHandler::closure(move |e| {
+                events.borrow_mut().push(e.lifecycle);
+                // build the content the first time the tab is shown
+                if e.lifecycle == Lifecycle::Shown && e.scene.get_children_ids(e.target).is_empty()
+                {
+                    let kid = crate::tests::make_simple_view(&"content".into());
+                    e.scene.add_view_to_parent(kid, e.target);
+                }
+            })),
+            ..Default::default()
+        });
+        assert!(scene.get_view(&content).is_none());
+        scene.show_view(&tab);
+        scene.show_view(&tab);
+        assert!(scene.get_view(&content).is_some());
+        scene.set_focused(&tab);
+        scene.set_focused(&content);
+        scene.hide_view(&tab);
+        scene.set_focused(&tab);
+        scene.remove_view(&tab);
+        assert_eq!(
+            *log.borrow(),
+            [
+                Lifecycle::Mounted,
+                Lifecycle::Shown,
+                Lifecycle::Focused,
+                Lifecycle::Unfocused,
+                Lifecycle::Hidden,
+                Lifecycle::Focused,
+                // removing the focused view unfocuses it first
+                Lifecycle::Unfocused,
+                Lifecycle::Unmounted,
+            ]
+        );
+        assert_eq!(scene.get_focused(), None);
+    }
+
+    #[test]
+    fn test_incremental_layout() {
+        let theme = MockDrawingContext::make_mock_theme();
+        let mut scene: Scene = Scene::new();
+        let row: ViewId = "row".into();
+        let label: ViewId = "label".into();
+        scene.add_view_to_root(
+            View {
+                name: row,
+                h_flex: Resize,
+                v_flex: Resize,
+                layout: Some(Handler::Fn(layout_hbox)),
+                ..Default::default()
+            }
+            .with_max_size(200, 40),
+        );
+        scene.add_view_to_parent(make_label("label", "Hi"), &row);
+        scene.add_view_to_parent(make_button(&"button".into(), "Go"), &row);
+        let runs = Rc::new(RefCell::new(0));
+        let counter = runs.clone();
+        scene.add_view_to_root(
+            View {
+                name: "other".into(),
+                layout: Some(LayoutHandler::closure(move |_| *counter.borrow_mut() += 1)),
+                ..Default::default()
+            }
+            .position_at(0, 150)
+            .with_size(50, 20),
+        );
+        layout_scene(&mut scene, &theme);
+        assert_eq!(*runs.borrow(), 1);
+        let button_before = scene.get_view(&"button".into()).unwrap().bounds;
+        let label_before = scene.get_view(&label).unwrap().bounds;
+        scene.dirty_rect = Bounds::new_empty();
+
+        // the label is intrinsic, so its row is laid out again, but nothing else
+        scene.set_title(&label, "Hello there");
+        layout_scene(&mut scene, &theme);
+        assert_eq!(*runs.borrow(), 1);
+        let button_after = scene.get_view(&"button".into()).unwrap().bounds;
+        let label_after = scene.get_view(&label).unwrap().bounds;
+        assert!(label_after.size.w > label_before.size.w);
+        assert!(button_after.position.x > button_before.position.x);
+        // only the label and the button it pushed along are repainted
+        assert_eq!(
+            scene.dirty_rect,
+            label_before
+                .union(label_after)
+                .union(button_before)
+                .union(button_after)
+        );
+
+        // when nothing moves, only the view that was marked is repainted
+        scene.dirty_rect = Bounds::new_empty();
+        scene.mark_view_layout_dirty(&row);
+        layout_scene(&mut scene, &theme);
+        assert_eq!(scene.dirty_rect, scene.get_view(&row).unwrap().bounds);
+
+        // hiding and adding views also stays out of the rest of the scene
+        scene.hide_view(&"button".into());
+        scene.add_view_to_parent(make_label("more", "More"), &row);
+        layout_scene(&mut scene, &theme);
+        assert_eq!(*runs.borrow(), 1);
+        assert!(scene.get_view(&"more".into()).unwrap().bounds.x() > 0);
+    }
 }
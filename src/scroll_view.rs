@@ -0,0 +1,425 @@
+use crate::geom::{Bounds, Insets, Point, Size};
+use crate::scene::{EventResult, Scene};
+use crate::view::Flex::Resize;
+use crate::view::{View, ViewId};
+use crate::{Action, DrawEvent, EventType, GuiEvent, Handler, LayoutEvent, MeasureEvent};
+use alloc::boxed::Box;
+
+/// The thickness of a scrollbar, kept clear in the padding of a scroll view.
+pub const SCROLLBAR_SIZE: i32 = 4;
+/// How far one wheel or encoder step scrolls, in pixels.
+pub const SCROLL_STEP: i32 = 10;
+const MIN_THUMB: i32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScrollViewState {
+    pub horizontal: bool,
+    pub vertical: bool,
+    /// Draw scrollbars in the right and bottom padding while the content overflows.
+    pub scrollbars: bool,
+    content: Size,
+}
+
+impl ScrollViewState {
+    pub fn new(horizontal: bool, vertical: bool) -> ScrollViewState {
+        ScrollViewState {
+            horizontal,
+            vertical,
+            scrollbars: true,
+            content: Size::new(0, 0),
+        }
+    }
+    /// The size of the children from the last layout, including their margins.
+    pub fn content_size(&self) -> Size {
+        self.content
+    }
+    fn max_offset(&self, viewport: Size) -> Point {
+        Point::new(
+            if self.horizontal {
+                (self.content.w - viewport.w).max(0)
+            } else {
+                0
+            },
+            if self.vertical {
+                (self.content.h - viewport.h).max(0)
+            } else {
+                0
+            },
+        )
+    }
+}
+
+/// A viewport onto a child that can be bigger than it. The child is laid out in the padded
+/// box and grows past it on the scrolling axes if it is `Intrinsic`. Scroll the content with
+/// `Scene::scroll_by`, scroll events, pointer drags, or by moving the focus to a hidden child.
+pub fn make_scroll_view<C>(name: &ViewId, horizontal: bool, vertical: bool) -> View<C> {
+    View {
+        name: *name,
+        title: name.as_str().into(),
+        h_flex: Resize,
+        v_flex: Resize,
+        padding: Insets::new(
+            0,
+            if vertical { SCROLLBAR_SIZE } else { 0 },
+            if horizontal { SCROLLBAR_SIZE } else { 0 },
+            0,
+        ),
+        clip_children: true,
+        state: Some(Box::new(ScrollViewState::new(horizontal, vertical))),
+        input: Some(Handler::Fn(input_scroll_view)),
+        layout: Some(Handler::Fn(layout_scroll_view)),
+        measure: Some(Handler::Fn(measure_scroll_view)),
+        draw: Some(Handler::Fn(draw_scroll_view)),
+        ..Default::default()
+    }
+}
+
+impl<C> Scene<C> {
+    /// Move the content of a scroll view, clamped to its edges. Returns false if it didn't
+    /// move or the view isn't a scroll view.
+    pub fn scroll_by(&mut self, name: &ViewId, delta: Point) -> bool {
+        let Some(view) = self.get_view_mut(name) else {
+            return false;
+        };
+        let viewport = view.bounds.size - view.padding;
+        let Some(max) = view
+            .get_state::<ScrollViewState>()
+            .map(|state| state.max_offset(viewport))
+        else {
+            return false;
+        };
+        let old = view.scroll_offset;
+        let new = Point::new(
+            (old.x + delta.x).clamp(0, max.x),
+            (old.y + delta.y).clamp(0, max.y),
+        );
+        if new == old {
+            return false;
+        }
+        view.scroll_offset = new;
+        self.mark_dirty_view(name);
+        true
+    }
+    pub fn scroll_to(&mut self, name: &ViewId, offset: Point) -> bool {
+        match self.get_view(name) {
+            Some(view) => self.scroll_by(name, offset - view.scroll_offset),
+            None => false,
+        }
+    }
+    /// Scroll every scroll view around the view just far enough to show it.
+    pub fn scroll_into_view(&mut self, name: &ViewId) {
+        let Some(mut rect) = self.get_view(name).map(|view| view.bounds) else {
+            return;
+        };
+        let mut current = *name;
+        while let Some(parent) = self.get_parent_for_view(&current).copied() {
+            let Some(view) = self.get_view_mut(&parent) else {
+                return;
+            };
+            if view.get_state::<ScrollViewState>().is_some() {
+                let viewport = view.bounds.size - view.padding;
+                let (padding, offset) = (view.padding, view.scroll_offset);
+                let wanted = Point::new(
+                    reveal(
+                        offset.x,
+                        rect.position.x - padding.left,
+                        rect.size.w,
+                        viewport.w,
+                    ),
+                    reveal(
+                        offset.y,
+                        rect.position.y - padding.top,
+                        rect.size.h,
+                        viewport.h,
+                    ),
+                );
+                self.scroll_by(&parent, wanted - offset);
+            }
+            if let Some(view) = self.get_view(&parent) {
+                rect = rect + view.bounds.position - view.scroll_offset;
+            }
+            current = parent;
+        }
+    }
+}
+
+/// The smallest change to `offset` that shows `len` pixels at `start`, preferring the start.
+fn reveal(offset: i32, start: i32, len: i32, viewport: i32) -> i32 {
+    let mut offset = offset;
+    if start + len - offset > viewport {
+        offset = start + len - viewport;
+    }
+    if start < offset {
+        offset = start;
+    }
+    offset
+}
+
+fn scroll_steps<C>(scene: &mut Scene<C>, name: &ViewId, x: i32, y: i32) -> Option<Action> {
+    if !scene.scroll_by(name, Point::new(x * SCROLL_STEP, y * SCROLL_STEP)) {
+        return None;
+    }
+    let offset = scene.get_view(name)?.scroll_offset;
+    Some(Action::ValueChanged(if y != 0 {
+        offset.y
+    } else {
+        offset.x
+    }))
+}
+
+/// Offer a scroll to the view and then its ancestors, until one of them scrolls.
+pub(crate) fn scroll_enclosing<C>(
+    scene: &mut Scene<C>,
+    start: &ViewId,
+    x: i32,
+    y: i32,
+) -> Option<EventResult> {
+    let mut current = Some(*start);
+    while let Some(name) = current {
+        if scene.is_enabled(&name)
+            && let Some(action) = scroll_steps(scene, &name, x, y)
+        {
+            return Some((name, action));
+        }
+        current = scene.get_parent_for_view(&name).copied();
+    }
+    None
+}
+
+fn input_scroll_view<C>(e: &mut GuiEvent<C>) -> Option<Action> {
+    if let EventType::Scroll(x, y) = &e.event_type {
+        return scroll_steps(e.scene, e.target, *x, *y);
+    }
+    None
+}
+
+/// The size of the view for its content, but no bigger than the space so the rest of it
+/// scrolls.
+fn scroll_view_size<C>(view: &View<C>, space: Size, content: Size) -> Size {
+    let size = view.resolve_size(space, content);
+    Size::new(size.w.min(space.w), size.h.min(space.h))
+}
+
+pub fn layout_scroll_view<C>(pass: &mut LayoutEvent<C>) {
+    let Some(view) = pass.scene.get_view_mut(pass.target) else {
+        return;
+    };
+    let padding = view.padding;
+    let viewport = pass.space - padding;
+
+    let mut content = Size::new(0, 0);
+    for kid in pass.scene.get_children_ids(pass.target) {
+        pass.layout_child(&kid, viewport);
+        if let Some(view) = pass.scene.get_view_mut(&kid) {
+            view.bounds.position = Point::new(
+                padding.left + view.margin.left,
+                padding.top + view.margin.top,
+            );
+            let outer = view.outer_size();
+            content.w = content.w.max(outer.w);
+            content.h = content.h.max(outer.h);
+        }
+    }
+
+    let Some(view) = pass.scene.get_view_mut(pass.target) else {
+        return;
+    };
+    view.bounds.size = scroll_view_size(view, pass.space, content);
+    if let Some(state) = view.get_state::<ScrollViewState>() {
+        state.content = content;
+    }
+    // the content may have shrunk under the current offset
+    pass.scene.scroll_by(pass.target, Point::zero());
+}
+
+/// The position and length of a scrollbar thumb along its track.
+fn thumb(offset: i32, content: i32, viewport: i32) -> (i32, i32) {
+    let len = (viewport * viewport / content).max(MIN_THUMB).min(viewport);
+    (offset * (viewport - len) / (content - viewport), len)
+}
+
+pub fn draw_scroll_view<C>(e: &mut DrawEvent<C>) {
+    let bounds = e.view.bounds;
+    e.ctx.fill_rect(&bounds, &e.theme.bg);
+    let Some(state) = e.view.get_state::<ScrollViewState>().copied() else {
+        return;
+    };
+    if !state.scrollbars {
+        return;
+    }
+    let viewport = bounds - e.view.padding;
+    let offset = e.view.scroll_offset;
+    if state.vertical && state.content.h > viewport.size.h {
+        let track = Bounds::new(
+            viewport.x2(),
+            viewport.position.y,
+            SCROLLBAR_SIZE,
+            viewport.size.h,
+        );
+        let (pos, len) = thumb(offset.y, state.content.h, viewport.size.h);
+        e.ctx.fill_rect(&track, &e.theme.panel_bg);
+        e.ctx.fill_rect(
+            &Bounds::new(
+                track.position.x,
+                track.position.y + pos,
+                SCROLLBAR_SIZE,
+                len,
+            ),
+            &e.theme.fg,
+        );
+    }
+    if state.horizontal && state.content.w > viewport.size.w {
+        let track = Bounds::new(
+            viewport.position.x,
+            viewport.y2(),
+            viewport.size.w,
+            SCROLLBAR_SIZE,
+        );
+        let (pos, len) = thumb(offset.x, state.content.w, viewport.size.w);
+        e.ctx.fill_rect(&track, &e.theme.panel_bg);
+        e.ctx.fill_rect(
+            &Bounds::new(
+                track.position.x + pos,
+                track.position.y,
+                len,
+                SCROLLBAR_SIZE,
+            ),
+            &e.theme.fg,
+        );
+    }
+}
+
+/// The measuring half of `layout_scroll_view`.
+pub fn measure_scroll_view<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    let viewport = e.space - view.padding;
+    let mut content = Size::new(0, 0);
+    for kid in e.scene.get_children_ids(e.target) {
+        let outer = e.measure_child(&kid, viewport);
+        content.w = content.w.max(outer.w);
+        content.h = content.h.max(outer.h);
+    }
+    scroll_view_size(view, e.space, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::{Point, Size};
+    use crate::layouts::layout_vbox;
+    use crate::scene::{
+        Scene, click_at, drag_at, draw_scene, event_at_focused, layout_scene, pick_at, press_at,
+    };
+    use crate::scroll_view::{ScrollViewState, make_scroll_view};
+    use crate::test::MockDrawingContext;
+    use crate::view::{View, ViewId};
+    use crate::{Action, EventType, Handler};
+    use alloc::vec;
+
+    const ITEMS: [&str; 10] = [
+        "item0", "item1", "item2", "item3", "item4", "item5", "item6", "item7", "item8", "item9",
+    ];
+
+    /// A 100x100 vertical scroll view over a column of ten 30px rows.
+    fn make_scroll_scene() -> Scene {
+        let mut scene = Scene::new();
+        let scroller: ViewId = "scroller".into();
+        let list: ViewId = "list".into();
+        scene.add_view_to_root(make_scroll_view(&scroller, false, true).with_max_size(100, 100));
+        scene.add_view_to_parent(
+            View {
+                name: list,
+                layout: Some(Handler::Fn(layout_vbox)),
+                ..Default::default()
+            },
+            &scroller,
+        );
+        for name in ITEMS {
+            scene.add_view_to_parent(
+                View {
+                    name: name.into(),
+                    input: Some(Handler::Fn(|e| {
+                        matches!(e.event_type, EventType::Tap(_)).then_some(Action::Generic)
+                    })),
+                    ..Default::default()
+                }
+                .with_size(96, 30),
+                &list,
+            );
+        }
+        let theme = MockDrawingContext::make_mock_theme();
+        layout_scene(&mut scene, &theme);
+        scene
+    }
+
+    fn offset(scene: &Scene) -> Point {
+        scene.get_view(&"scroller".into()).unwrap().scroll_offset
+    }
+
+    #[test]
+    fn test_scroll_view() {
+        let mut scene = make_scroll_scene();
+        let scroller: ViewId = "scroller".into();
+        let content = scene
+            .get_view_state::<ScrollViewState>(&scroller)
+            .unwrap()
+            .content_size();
+        assert_eq!(content, Size::new(96, 300));
+
+        // picking goes through the offset, and the scrollbar gutter hides the content
+        assert!(scene.scroll_by(&scroller, Point::new(0, 50)));
+        let picks = pick_at(&mut scene, &Point::new(20, 20));
+        assert_eq!(picks.last().unwrap().0, "item2".into());
+        assert_eq!(picks.last().unwrap().1, Point::new(20, 70));
+        let picks = pick_at(&mut scene, &Point::new(98, 20));
+        assert_eq!(picks.last().unwrap().0, scroller);
+
+        // offsets stop at the ends of the content
+        assert!(scene.scroll_by(&scroller, Point::new(30, 1000)));
+        assert_eq!(offset(&scene), Point::new(0, 200));
+        assert!(!scene.scroll_by(&scroller, Point::new(0, 1)));
+
+        // a scroll the focused row ignores moves the scroll view around it
+        scene.set_focused(&"item5".into());
+        let result = event_at_focused(&mut scene, &EventType::Scroll(0, -3), &mut ());
+        assert!(matches!(result, Some((id, Action::ValueChanged(170))) if id == scroller));
+
+        // moving the focus to a hidden row brings it into view
+        scene.set_focused(&"item8".into());
+        scene.focus_next();
+        assert_eq!(offset(&scene), Point::new(0, 200));
+        scene.focus_next();
+        scene.focus_next();
+        assert_eq!(scene.get_focused(), Some("item0".into()));
+        assert_eq!(offset(&scene), Point::new(0, 0));
+
+        // clipping is undone once the children are drawn
+        let theme = MockDrawingContext::make_mock_theme();
+        scene.mark_dirty_all();
+        let mut ctx = MockDrawingContext::new(&scene);
+        draw_scene(&mut scene, &mut ctx, &theme);
+        assert_eq!(ctx.clip_rect, scene.bounds);
+    }
+
+    #[test]
+    fn test_drag_scroll() {
+        let mut scene = make_scroll_scene();
+        press_at(&mut scene, Point::new(20, 80));
+        assert_eq!(scene.get_pressed(), Some("item2".into()));
+        // a small wobble is still a tap
+        drag_at(&mut scene, Point::new(21, 78));
+        assert_eq!(offset(&scene), Point::new(0, 0));
+        assert_eq!(scene.get_pressed(), Some("item2".into()));
+        // dragging up scrolls the content up with the pointer
+        drag_at(&mut scene, Point::new(20, 40));
+        assert_eq!(offset(&scene), Point::new(0, 40));
+        assert_eq!(scene.get_pressed(), None);
+        drag_at(&mut scene, Point::new(20, 30));
+        assert_eq!(offset(&scene), Point::new(0, 50));
+        // the release after a drag doesn't tap
+        assert!(click_at(&mut scene, &vec![], Point::new(20, 30), &mut ()).is_none());
+        let result = click_at(&mut scene, &vec![], Point::new(20, 30), &mut ());
+        assert!(matches!(result, Some((id, Action::Generic)) if id == "item2".into()));
+    }
+}
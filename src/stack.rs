@@ -0,0 +1,253 @@
+use crate::geom::{Point, Size};
+use crate::scene::Scene;
+use crate::view::{View, ViewId};
+use crate::{Handler, LayoutEvent, MeasureEvent};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use hashbrown::HashMap;
+
+/// Where a child of a stack is pinned inside the parent's padded box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+    /// Stretch the child over the whole box.
+    Fill,
+}
+
+impl Anchor {
+    /// The fraction of the free space before the child on each axis, in halves.
+    fn halves(&self) -> (i32, i32) {
+        match self {
+            Anchor::TopLeft | Anchor::Fill => (0, 0),
+            Anchor::Top => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::Left => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::Right => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::Bottom => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StackConstraint {
+    pub anchor: Anchor,
+    /// Moves the child from its anchored position, right and down for positive values.
+    pub offset: Point,
+}
+
+/// Children of a stack are layered in the order they were added, so later children draw on
+/// top. Children without a constraint are pinned to the top left.
+#[derive(Default)]
+pub struct StackLayoutState {
+    pub constraints: HashMap<ViewId, StackConstraint>,
+}
+
+impl StackLayoutState {
+    pub fn new() -> StackLayoutState {
+        StackLayoutState {
+            constraints: HashMap::new(),
+        }
+    }
+    pub fn anchor(&mut self, name: &ViewId, anchor: Anchor) -> Option<StackConstraint> {
+        self.anchor_with_offset(name, anchor, Point::new(0, 0))
+    }
+    pub fn anchor_with_offset(
+        &mut self,
+        name: &ViewId,
+        anchor: Anchor,
+        offset: Point,
+    ) -> Option<StackConstraint> {
+        self.constraints
+            .insert(*name, StackConstraint { anchor, offset })
+    }
+}
+
+pub fn make_stack_panel<C>(name: &ViewId) -> View<C> {
+    View {
+        name: *name,
+        title: name.as_str().into(),
+        state: Some(Box::new(StackLayoutState::new())),
+        layout: Some(Handler::Fn(layout_stack)),
+        measure: Some(Handler::Fn(measure_stack)),
+        ..Default::default()
+    }
+}
+
+/// Each child with its constraint, pinned to the top left if it has none.
+fn stack_kids<C>(scene: &Scene<C>, name: &ViewId) -> Vec<(ViewId, StackConstraint)> {
+    let constraints = scene
+        .get_view(name)
+        .and_then(|view| view.get_state_ref::<StackLayoutState>());
+    scene
+        .get_children_ids(name)
+        .into_iter()
+        .map(|kid| {
+            let cons = constraints
+                .and_then(|state| state.constraints.get(&kid))
+                .copied()
+                .unwrap_or(StackConstraint {
+                    anchor: Anchor::TopLeft,
+                    offset: Point::new(0, 0),
+                });
+            (kid, cons)
+        })
+        .collect()
+}
+
+/// The outer size of the biggest pinned child. Filling children don't count, since they
+/// take whatever size the stack ends up.
+fn stack_content(
+    inner: Size,
+    kids: &[(ViewId, StackConstraint)],
+    measure: &mut dyn FnMut(&ViewId, Size) -> Size,
+) -> Size {
+    let mut content = Size::new(0, 0);
+    for (kid, cons) in kids {
+        if cons.anchor != Anchor::Fill {
+            let size = measure(kid, inner);
+            content.w = content.w.max(size.w);
+            content.h = content.h.max(size.h);
+        }
+    }
+    content
+}
+
+pub fn layout_stack<C>(pass: &mut LayoutEvent<C>) {
+    let kids = stack_kids(pass.scene, pass.target);
+    let Some(view) = pass.scene.get_view(pass.target) else {
+        return;
+    };
+    let padding = view.padding;
+    let space_inner = pass.space - padding;
+
+    // measure the pinned children first, so a stack sized to its content can wrap them
+    let content = stack_content(space_inner, &kids, &mut |kid, space| {
+        pass.measure_child(kid, space)
+    });
+    let Some(view) = pass.scene.get_view_mut(pass.target) else {
+        return;
+    };
+    let size = view.resolve_size(pass.space, content);
+    view.bounds.size = size;
+    let inner = size - padding;
+
+    for (kid, cons) in &kids {
+        let space = if cons.anchor == Anchor::Fill {
+            inner
+        } else {
+            space_inner
+        };
+        pass.layout_child(kid, space);
+        let Some(view) = pass.scene.get_view_mut(kid) else {
+            continue;
+        };
+        if cons.anchor == Anchor::Fill {
+            view.bounds.size = view.clamp_size(inner - view.margin);
+        }
+        let outer = view.outer_size();
+        let (hx, hy) = cons.anchor.halves();
+        view.bounds.position = Point::new(
+            padding.left + view.margin.left + (inner.w - outer.w) * hx / 2 + cons.offset.x,
+            padding.top + view.margin.top + (inner.h - outer.h) * hy / 2 + cons.offset.y,
+        );
+    }
+}
+
+/// The measuring half of `layout_stack`.
+pub fn measure_stack<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return Size::new(0, 0);
+    };
+    let kids = stack_kids(e.scene, e.target);
+    let content = stack_content(e.space - view.padding, &kids, &mut |kid, space| {
+        e.measure_child(kid, space)
+    });
+    view.resolve_size(e.space, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Handler;
+    use crate::geom::{Bounds, Insets, Point, Size};
+    use crate::layouts::layout_std_panel;
+    use crate::scene::{Scene, layout_scene};
+    use crate::stack::{Anchor, StackLayoutState, make_stack_panel};
+    use crate::test::MockDrawingContext;
+    use crate::view::Flex::Resize;
+    use crate::view::{View, ViewId};
+    use alloc::boxed::Box;
+
+    fn bounds(scene: &Scene, name: &'static str) -> Bounds {
+        scene.get_view(&ViewId::new(name)).unwrap().bounds
+    }
+
+    #[test]
+    fn test_stack_anchors() {
+        let theme = MockDrawingContext::make_mock_theme();
+        let mut scene = Scene::new();
+        let stack_id = ViewId::new("stack");
+        let mut stack = make_stack_panel(&stack_id);
+        stack.padding = Insets::new_same(10);
+        let mut layout = StackLayoutState::new();
+        let fixed = |name: &'static str, w: i32, h: i32| {
+            View {
+                name: name.into(),
+                layout: None,
+                ..Default::default()
+            }
+            .with_size(w, h)
+        };
+        scene.add_view_to_parent(
+            View {
+                name: "background".into(),
+                h_flex: Resize,
+                v_flex: Resize,
+                layout: Some(Handler::Fn(layout_std_panel)),
+                ..Default::default()
+            },
+            &stack_id,
+        );
+        layout.anchor(&"background".into(), Anchor::Fill);
+        scene.add_view_to_parent(fixed("icon", 40, 30), &stack_id);
+        layout.anchor(&"icon".into(), Anchor::Center);
+        scene.add_view_to_parent(fixed("badge", 10, 10), &stack_id);
+        layout.anchor_with_offset(&"badge".into(), Anchor::TopRight, Point::new(-2, 2));
+        scene.add_view_to_parent(fixed("corner", 8, 8), &stack_id);
+        stack.state = Some(Box::new(layout));
+        scene.add_view_to_root(stack);
+        layout_scene(&mut scene, &theme);
+
+        // an intrinsic stack wraps its biggest pinned child
+        assert_eq!(bounds(&scene, "stack").size, Size::new(60, 50));
+        assert_eq!(bounds(&scene, "background"), Bounds::new(10, 10, 40, 30));
+        assert_eq!(bounds(&scene, "icon"), Bounds::new(10, 10, 40, 30));
+        assert_eq!(bounds(&scene, "badge"), Bounds::new(38, 12, 10, 10));
+        // no constraint means top left
+        assert_eq!(bounds(&scene, "corner"), Bounds::new(10, 10, 8, 8));
+
+        // a floating button stays pinned to the corner as the stack grows
+        if let Some(view) = scene.get_view_mut(&stack_id) {
+            view.h_flex = Resize;
+            view.v_flex = Resize;
+        }
+        if let Some(state) = scene.get_view_state::<StackLayoutState>(&stack_id) {
+            state.anchor(&"corner".into(), Anchor::BottomRight);
+        }
+        scene.mark_layout_dirty();
+        layout_scene(&mut scene, &theme);
+        assert_eq!(bounds(&scene, "corner"), Bounds::new(182, 182, 8, 8));
+        assert_eq!(bounds(&scene, "background"), Bounds::new(10, 10, 180, 180));
+        assert_eq!(bounds(&scene, "icon"), Bounds::new(80, 85, 40, 30));
+    }
+}
@@ -1,102 +1,63 @@
-use crate::geom::{Bounds, Insets};
+use crate::geom::{Bounds, Insets, Point};
+use crate::layouts::{measure_std_panel, mirror_children};
 use crate::scene::Scene;
 use crate::toggle_group::{input_toggle_group, make_toggle_group};
 use crate::view::Flex::Resize;
 use crate::view::{Flex, View, ViewId};
-use crate::{Action, LayoutEvent};
-use alloc::boxed::Box;
-use alloc::string::String;
-use alloc::vec;
+use crate::{Action, Handler, InputHandler, LayoutEvent};
 use alloc::vec::Vec;
-use hashbrown::HashMap;
 use log::info;
 
-pub struct LayoutPanelState {
-    data: Vec<String>,
-    contents: HashMap<String, ViewId>,
-}
-
-impl LayoutPanelState {
-    pub fn register_panel(&mut self, tab_name: &str, content_id: &ViewId) {
-        info!("registering panel {tab_name} with content {content_id}");
-        self.data.push(tab_name.into());
-        self.contents.insert(tab_name.into(), *content_id);
-    }
-}
-
-pub fn make_tabbed_panel(
+/// Make a panel with a strip of tabs along the top. Each tab is a title and the id of the
+/// content view it shows. The content views are added to the panel by the caller.
+pub fn make_tabbed_panel<C>(
     name: &ViewId,
-    data: Vec<&str>,
+    tabs_id: &ViewId,
+    tabs: Vec<(&str, ViewId)>,
     selected: usize,
-    scene: &mut Scene,
-) -> View {
-    let state = LayoutPanelState {
-        data: vec![],
-        contents: HashMap::new(),
-    };
-
-    let tabs_id = ViewId::new("tabs");
-    let mut tabs = make_toggle_group(&tabs_id, data, selected);
-    tabs.input = Some(|e| {
-        info!("got input");
-        let res = input_toggle_group(e);
-        if let Some(action) = res {
-            info!("action is {:?}", action);
-            let Some(container) = e.scene.get_parent_for_view(e.target) else {
-                return None;
-            };
-            let container = container.clone();
-
-            // hide all the children
-            for kid in e.scene.get_children_ids(&container) {
-                if kid.as_str() != "tabs" {
-                    e.scene.hide_view(&kid);
-                }
-            }
-
-            // then make the newly selected one visible
-            match action {
-                Action::Generic => {}
-                Action::Command(cmd) => {
-                    let panel_name = if let Some(state) =
-                        e.scene.get_view_state::<LayoutPanelState>(&container)
-                    {
-                        if let Some(panel) = state.contents.get(&cmd) {
-                            Some(panel.clone())
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    };
-                    info!("found the panel name {panel_name:?}");
-                    if let Some(panel) = panel_name {
-                        info!("setting the panel {}", panel);
-                        e.scene.show_view(&panel);
-                    }
-                }
+    scene: &mut Scene<C>,
+) -> View<C> {
+    let container = *name;
+    let strip_id = *tabs_id;
+    let titles = tabs.iter().map(|(title, _)| *title).collect();
+    let panels: Vec<ViewId> = tabs.iter().map(|(_, panel)| *panel).collect();
+    let mut strip = make_toggle_group(tabs_id, titles, selected);
+    strip.input = Some(InputHandler::closure(move |e| {
+        let Some(Action::Selected(index)) = input_toggle_group(e) else {
+            return None;
+        };
+        info!("switching {container} to tab {index}");
+        // hide all the children
+        for kid in e.scene.get_children_ids(&container) {
+            if kid != strip_id {
+                e.scene.hide_view(&kid);
             }
         }
-        return None;
-    });
-    scene.add_view_to_parent(tabs, name);
+        // then make the newly selected one visible
+        if let Some(panel) = panels.get(index) {
+            e.scene.show_view(panel);
+        }
+        None
+    }));
+    scene.add_view_to_parent(strip, name);
 
     View {
         name: *name,
         bounds: Bounds::new(10, 10, 320 - 20, 180),
         h_flex: Flex::Intrinsic,
         v_flex: Flex::Intrinsic,
-        draw: Some(|e| {
+        draw: Some(Handler::Fn(|e| {
             e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
             e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
-        }),
-        state: Some(Box::new(state)),
-        layout: Some(layout_tabbed_panel),
+        })),
+        layout: Some(Handler::Fn(layout_tabbed_panel)),
+        measure: Some(Handler::Fn(measure_std_panel)),
         ..Default::default()
     }
 }
 
-pub fn layout_tabbed_panel(pass: &mut LayoutEvent) {
+/// Lays out the first child as the tab strip and fills the remaining space with the others.
+pub fn layout_tabbed_panel<C>(pass: &mut LayoutEvent<C>) {
     if let Some(view) = pass.scene.get_view_mut(&pass.target) {
         // layout self
         if view.h_flex == Resize {
@@ -106,37 +67,42 @@ pub fn layout_tabbed_panel(pass: &mut LayoutEvent) {
             view.bounds.size.h = pass.space.h;
         }
 
-        // layout tabs
         let space = view.bounds.size.clone();
-        let tabs_id: ViewId = "tabs".into();
-        pass.layout_child(&tabs_id, space);
+        let kids = pass.scene.get_children_ids(pass.target);
+        let Some((tabs_id, panels)) = kids.split_first() else {
+            return;
+        };
+        // layout tabs
+        pass.layout_child(tabs_id, space);
 
         // layout content panels
-        if let Some(view) = pass.scene.get_view(&tabs_id) {
-            let insets = Insets::new(view.bounds.size.h, 0, 0, 0);
-            for kid in &pass.scene.get_children_ids(&pass.target) {
-                if kid == &tabs_id {
-                    continue;
-                }
+        if let Some(view) = pass.scene.get_view_mut(tabs_id) {
+            view.bounds.position = Point::new(view.margin.left, view.margin.top);
+            let insets = Insets::new(view.outer_size().h, 0, 0, 0);
+            for kid in panels {
                 pass.layout_child(kid, space - insets);
                 if let Some(view) = pass.scene.get_view_mut(kid) {
-                    view.bounds.position.y = insets.top;
+                    view.bounds.position =
+                        Point::new(view.margin.left, insets.top + view.margin.top);
                 }
             }
         }
     }
+    mirror_children(pass.scene, pass.target);
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::geom::{Bounds, Size};
+    use crate::Handler;
+    use crate::geom::{Bounds, Point, Size};
     use crate::layouts::{layout_hbox, layout_std_panel, layout_vbox};
-    use crate::scene::{Scene, layout_scene};
-    use crate::tabbed_panel::layout_tabbed_panel;
+    use crate::scene::{Scene, click_at, layout_scene};
+    use crate::tabbed_panel::{layout_tabbed_panel, make_tabbed_panel};
     use crate::test::MockDrawingContext;
     use crate::toggle_group::layout_toggle_group;
     use crate::view::Align::{Center, End, Start};
     use crate::view::{Flex, View, ViewId};
+    use alloc::vec;
 
     #[test]
     fn test_complex_tabbed_panels() {
@@ -151,7 +117,7 @@ mod tests {
             };
             tabbed_panel_view.h_flex = Flex::Resize;
             tabbed_panel_view.v_flex = Flex::Resize;
-            tabbed_panel_view.layout = Some(layout_tabbed_panel);
+            tabbed_panel_view.layout = Some(Handler::Fn(layout_tabbed_panel));
             scene.add_view_to_parent(tabbed_panel_view, &scene.root_id());
 
             // tab panel tabs has intrisic height but flex width
@@ -161,7 +127,7 @@ mod tests {
             };
             tabbed_panel_tabs.h_flex = Flex::Resize;
             tabbed_panel_tabs.v_flex = Flex::Intrinsic;
-            tabbed_panel_tabs.layout = Some(layout_toggle_group);
+            tabbed_panel_tabs.layout = Some(Handler::Fn(layout_toggle_group));
             scene.add_view_to_parent(tabbed_panel_tabs, &tabbed_panel);
         }
 
@@ -173,7 +139,7 @@ mod tests {
             let mut view = crate::layouts::tests::make_standard_view(&tab1);
             view.h_flex = Flex::Resize;
             view.v_flex = Flex::Resize;
-            view.layout = Some(layout_hbox);
+            view.layout = Some(Handler::Fn(layout_hbox));
             scene.add_view_to_parent(view, &tabbed_panel);
 
             let b1: ViewId = "tab1_button1".into();
@@ -181,7 +147,7 @@ mod tests {
                 let mut button = crate::layouts::tests::make_standard_view(&b1);
                 button.title = "abc".into();
                 button.v_align = Start;
-                button.layout = Some(crate::layouts::tests::layout_button);
+                button.layout = Some(Handler::Fn(crate::layouts::tests::layout_button));
                 scene.add_view_to_parent(button, &tab1);
             }
 
@@ -189,7 +155,7 @@ mod tests {
             {
                 let mut button = crate::layouts::tests::make_standard_view(&b2);
                 button.v_align = Center;
-                button.layout = Some(crate::layouts::tests::layout_button);
+                button.layout = Some(Handler::Fn(crate::layouts::tests::layout_button));
                 scene.add_view_to_parent(button, &tab1);
             }
 
@@ -197,7 +163,7 @@ mod tests {
             {
                 let mut button = crate::layouts::tests::make_standard_view(&b3);
                 button.v_align = End;
-                button.layout = Some(crate::layouts::tests::layout_button);
+                button.layout = Some(Handler::Fn(crate::layouts::tests::layout_button));
                 scene.add_view_to_parent(button, &tab1);
             }
         }
@@ -209,7 +175,7 @@ mod tests {
             let mut view = crate::layouts::tests::make_standard_view(&tab2);
             view.h_flex = Flex::Resize;
             view.v_flex = Flex::Resize;
-            view.layout = Some(layout_vbox);
+            view.layout = Some(Handler::Fn(layout_vbox));
             scene.add_view_to_parent(view, &tabbed_panel);
 
             let b1: ViewId = "tab2_button1".into();
@@ -219,7 +185,7 @@ mod tests {
                 b1_view.h_flex = Flex::Resize;
                 b1_view.v_flex = Flex::Resize;
                 b1_view.title = "b11".into();
-                b1_view.layout = Some(layout_std_panel);
+                b1_view.layout = Some(Handler::Fn(layout_std_panel));
                 scene.add_view_to_parent(b1_view, &tab2);
             }
 
@@ -230,7 +196,7 @@ mod tests {
                 b2_view.h_flex = Flex::Intrinsic;
                 b2_view.v_flex = Flex::Intrinsic;
                 b2_view.title = "b11".into();
-                b2_view.layout = Some(crate::layouts::tests::layout_button);
+                b2_view.layout = Some(Handler::Fn(crate::layouts::tests::layout_button));
                 scene.add_view_to_parent(b2_view, &tab2);
             }
         }
@@ -241,7 +207,7 @@ mod tests {
             let mut view = crate::layouts::tests::make_standard_view(&tab3);
             view.h_flex = Flex::Resize;
             view.v_flex = Flex::Resize;
-            view.layout = Some(layout_std_panel);
+            view.layout = Some(Handler::Fn(layout_std_panel));
             scene.add_view_to_parent(view, &tabbed_panel);
         }
 
@@ -289,4 +255,36 @@ mod tests {
             Some(Bounds::new(170, 170, 30, 10))
         );
     }
+
+    #[test]
+    fn test_tab_switching() {
+        let mut scene = Scene::new();
+        let panel: ViewId = "panel".into();
+        let first: ViewId = "first".into();
+        let second: ViewId = "second".into();
+        let mut view = make_tabbed_panel(
+            &panel,
+            &"panel-tabs".into(),
+            vec![("one", first), ("two", second)],
+            0,
+            &mut scene,
+        );
+        view.h_flex = Flex::Resize;
+        view.v_flex = Flex::Resize;
+        scene.add_view_to_root(view);
+        for id in [first, second] {
+            let mut content = crate::layouts::tests::make_standard_view(&id);
+            content.h_flex = Flex::Resize;
+            content.v_flex = Flex::Resize;
+            content.layout = Some(Handler::Fn(layout_std_panel));
+            scene.add_view_to_parent(content, &panel);
+        }
+        let theme = MockDrawingContext::make_mock_theme();
+        layout_scene(&mut scene, &theme);
+
+        // tap the second tab
+        click_at(&mut scene, &vec![], Point::new(160, 15), &mut ());
+        assert!(!scene.get_view(&first).unwrap().visible);
+        assert!(scene.get_view(&second).unwrap().visible);
+    }
 }
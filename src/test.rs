@@ -1,6 +1,7 @@
 use crate::geom::{Bounds, Point};
 use crate::gfx::{DrawingContext, TextStyle};
 use crate::scene::Scene;
+use crate::view::Direction;
 use crate::{Theme, util};
 use embedded_graphics::Drawable;
 use embedded_graphics::geometry::Point as EPoint;
@@ -16,14 +17,16 @@ pub struct MockDrawingContext {
     pub clip_rect: Bounds,
     pub display: MockDisplay<Rgb565>,
     offset: Point,
+    pub direction: Direction,
 }
 
 impl MockDrawingContext {
-    pub fn new(scene: &Scene) -> MockDrawingContext {
+    pub fn new<C>(scene: &Scene<C>) -> MockDrawingContext {
         let mut ctx: MockDrawingContext = MockDrawingContext {
             clip_rect: scene.dirty_rect,
             display: MockDisplay::new(),
             offset: Point::new(0, 0),
+            direction: Direction::Ltr,
         };
         ctx.display.set_allow_out_of_bounds_drawing(true);
         ctx.display.set_allow_overdraw(true);
@@ -36,6 +39,10 @@ impl MockDrawingContext {
             selected_bg: Rgb565::WHITE,
             selected_fg: Rgb565::BLACK,
             panel_bg: Rgb565::CSS_GRAY,
+            disabled_bg: Rgb565::CSS_LIGHT_GRAY,
+            disabled_fg: Rgb565::CSS_DARK_GRAY,
+            pressed_bg: Rgb565::CSS_DARK_GRAY,
+            hover_bg: Rgb565::CSS_LIGHT_GRAY,
             font: FONT_6X10,
             bold_font: FONT_7X13_BOLD,
         }
@@ -72,7 +79,7 @@ impl DrawingContext for MockDrawingContext {
         let mut pt = embedded_graphics::geometry::Point::new(bounds.position.x, bounds.position.y);
         pt.y += bounds.size.h / 2;
         pt.y += (style.font.baseline as i32) / 2;
-        let w = (style.font.character_size.width as i32) * (text.len() as i32);
+        let w = (style.font.character_size.width as i32) * (text.chars().count() as i32);
         pt.x += (bounds.size.w - w) / 2;
         Text::new(text, pt, style).draw(&mut self.display).unwrap();
     }
@@ -82,4 +89,18 @@ impl DrawingContext for MockDrawingContext {
     fn translate(&mut self, offset: &Point) {
         self.offset = self.offset + *offset;
     }
+
+    fn clip_to(&mut self, bounds: &Bounds) -> Bounds {
+        let old = self.clip_rect;
+        self.clip_rect = self.clip_rect.intersection(*bounds + self.offset);
+        old
+    }
+
+    fn restore_clip(&mut self, clip: Bounds) {
+        self.clip_rect = clip;
+    }
+
+    fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
 }
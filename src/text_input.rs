@@ -1,10 +1,9 @@
 use crate::geom::Bounds;
 use crate::gfx::TextStyle;
-use crate::view::{Align, View, ViewId};
-use crate::{Action, DrawEvent, EventType, GuiEvent, KeyboardAction};
+use crate::view::{Align, Direction, View, ViewId};
+use crate::{Action, DrawEvent, EventType, GuiEvent, Handler, Key};
 use alloc::boxed::Box;
 use alloc::string::String;
-use core::str::Chars;
 use log::info;
 
 pub struct TextInputState {
@@ -13,34 +12,64 @@ pub struct TextInputState {
 }
 
 impl TextInputState {
+    // the cursor counts chars, so convert it before touching the utf-8 string
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.text.len())
+    }
+    fn char_count(&self) -> usize {
+        self.text.chars().count()
+    }
     fn cursor_back(&mut self) {
         if self.cursor > 0 {
             self.cursor -= 1;
         }
     }
     fn cursor_forward(&mut self) {
-        if self.cursor < self.text.len() {
+        if self.cursor < self.char_count() {
             self.cursor += 1;
         }
     }
+    fn cursor_home(&mut self) {
+        self.cursor = 0;
+    }
+    fn cursor_end(&mut self) {
+        self.cursor = self.char_count();
+    }
     fn delete_back(&mut self) {
-        if self.cursor > 0 && self.cursor <= self.text.len() {
+        if self.cursor > 0 && self.cursor <= self.char_count() {
             self.cursor -= 1;
-            self.text.remove(self.cursor);
+            let n = self.byte_index(self.cursor);
+            self.text.remove(n);
         }
     }
     fn delete_forward(&mut self) {
-        if self.cursor < self.text.len() {
-            self.text.remove(self.cursor);
+        if self.cursor < self.char_count() {
+            let n = self.byte_index(self.cursor);
+            self.text.remove(n);
         }
     }
     fn insert_char(&mut self, key: char) {
-        self.text.insert(self.cursor, key);
+        let n = self.byte_index(self.cursor);
+        self.text.insert(n, key);
         self.cursor += 1;
     }
 }
 
-fn draw_text_input(e: &mut DrawEvent) {
+/// Where the cursor goes, lined up with the text as `fill_text` places it: against the left
+/// edge, or the right edge for right to left.
+fn cursor_x(bounds: &Bounds, state: &TextInputState, char_width: i32, direction: Direction) -> i32 {
+    let start = match Align::Start.for_direction(direction) {
+        Align::End => bounds.size.w - char_width * state.char_count() as i32 - 5,
+        _ => 5,
+    };
+    bounds.position.x + start + state.cursor as i32 * char_width
+}
+
+fn draw_text_input<C>(e: &mut DrawEvent<C>) {
     e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
     e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
     let style = TextStyle::new(&e.theme.font, &e.theme.fg).with_halign(Align::Start);
@@ -54,12 +83,11 @@ fn draw_text_input(e: &mut DrawEvent) {
         if focused == &e.view.name {
             e.ctx.stroke_rect(&e.view.bounds.contract(2), &e.theme.fg);
             if let Some(state) = e.view.get_state::<TextInputState>() {
-                let n = state.cursor as i32;
                 let w = e.theme.font.character_size.width as i32;
                 let h = e.theme.font.character_size.height as i32;
                 e.ctx.fill_rect(
                     &Bounds::new(
-                        e.view.bounds.position.x + n * w + 5,
+                        cursor_x(&bounds, state, w, e.direction),
                         e.view.bounds.position.y + 5,
                         1,
                         h + 4,
@@ -71,40 +99,42 @@ fn draw_text_input(e: &mut DrawEvent) {
     }
 }
 
-fn input_text_input(event: &mut GuiEvent) -> Option<Action> {
+fn input_text_input<C>(event: &mut GuiEvent<C>) -> Option<Action> {
     info!("text input got event {:?}", event.event_type);
     match &event.event_type {
-        EventType::Keyboard(key) => {
+        EventType::Key(key) => {
+            if !key.is_pressed() {
+                return None;
+            }
+            let mut action = None;
             if let Some(state) = event.scene.get_view_state::<TextInputState>(event.target) {
-                match *key {
-                    8 => {
-                        state.delete_back();
-                    }
-                    13 => {
+                let before = state.text.len();
+                match key.key {
+                    Key::Left => state.cursor_back(),
+                    Key::Right => state.cursor_forward(),
+                    Key::Home => state.cursor_home(),
+                    Key::End => state.cursor_end(),
+                    Key::Backspace => state.delete_back(),
+                    Key::Delete => state.delete_forward(),
+                    // emacs style forward delete
+                    Key::Char('d') if key.modifiers.ctrl => state.delete_forward(),
+                    Key::Return => {
                         info!("doing return");
-                        return Some(Action::Command("Completed".into()));
+                        return Some(Action::Submitted(state.text.clone()));
                     }
                     _ => {
-                        state.insert_char(*key as char);
+                        if let Some(ch) = key.typed_text() {
+                            state.insert_char(ch);
+                        }
                     }
                 }
-                info!("done");
-            }
-            event.scene.mark_dirty_view(event.target);
-        }
-        EventType::KeyboardAction(act) => {
-            if let Some(state) = event.scene.get_view_state::<TextInputState>(event.target) {
-                match act {
-                    KeyboardAction::Left => state.cursor_back(),
-                    KeyboardAction::Right => state.cursor_forward(),
-                    KeyboardAction::Up => {}
-                    KeyboardAction::Down => {}
-                    KeyboardAction::Backspace => state.delete_back(),
-                    KeyboardAction::Delete => state.delete_forward(),
-                    KeyboardAction::Return => {}
+                // every edit either adds or removes a char, so the length tells us if it changed
+                if state.text.len() != before {
+                    action = Some(Action::TextChanged(state.text.clone()));
                 }
-                event.scene.mark_dirty_view(event.target);
             }
+            event.scene.mark_dirty_view(event.target);
+            return action;
         }
         EventType::Tap(_pt) => {
             event.scene.set_focused(event.target);
@@ -114,7 +144,7 @@ fn input_text_input(event: &mut GuiEvent) -> Option<Action> {
     None
 }
 
-pub fn make_text_input(name: &'static str, title: &str) -> View {
+pub fn make_text_input<C>(name: &'static str, title: &str) -> View<C> {
     View {
         name: ViewId::new(name),
         title: title.into(),
@@ -122,15 +152,79 @@ pub fn make_text_input(name: &'static str, title: &str) -> View {
         visible: true,
         state: Some(Box::new(TextInputState {
             text: title.into(),
-            cursor: title.len(),
+            cursor: title.chars().count(),
         })),
-        input: Some(input_text_input),
-        layout: Some(|_e| {
-            // if let Some(view) = e.scene.get_view_mut(e.target) {
-            //     view.bounds = util::calc_bounds(view.bounds, e.theme.bold_font, &view.title);
-            // }
-        }),
-        draw: Some(draw_text_input),
+        input: Some(Handler::Fn(input_text_input)),
+        draw: Some(Handler::Fn(draw_text_input)),
         ..Default::default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::geom::Bounds;
+    use crate::scene::{Scene, event_at_focused};
+    use crate::text_input::{TextInputState, cursor_x, make_text_input};
+    use crate::view::Direction;
+    use crate::{Action, EventType, Key, KeyEvent, Modifiers};
+
+    fn send(scene: &mut Scene, key: KeyEvent) -> Option<Action> {
+        event_at_focused(scene, &EventType::Key(key), &mut ()).map(|(_, action)| action)
+    }
+
+    #[test]
+    fn test_text_input_editing() {
+        let mut scene = Scene::new();
+        let input = make_text_input("input", "añb");
+        let name = input.name;
+        scene.add_view_to_root(input);
+        scene.set_focused(&name);
+
+        // cursor starts at the end, counted in chars not bytes
+        send(&mut scene, KeyEvent::pressed(Key::Left));
+        send(&mut scene, KeyEvent::pressed(Key::Backspace));
+        send(&mut scene, KeyEvent::text('ö'));
+        // held keys repeat
+        send(&mut scene, KeyEvent::text('!').with_repeat(true));
+        send(&mut scene, KeyEvent::pressed(Key::Home));
+        send(
+            &mut scene,
+            KeyEvent::text('d').with_modifiers(Modifiers::ctrl()),
+        );
+        send(&mut scene, KeyEvent::released(Key::Backspace));
+        let state = scene.get_view_state::<TextInputState>(&name).unwrap();
+        assert_eq!(state.text, "ö!b");
+        assert_eq!(state.cursor, 0);
+    }
+
+    #[test]
+    fn test_text_input_actions() {
+        let mut scene = Scene::new();
+        let input = make_text_input("input", "ab");
+        let name = input.name;
+        scene.add_view_to_root(input);
+        scene.set_focused(&name);
+
+        let res = send(&mut scene, KeyEvent::text('c'));
+        assert!(matches!(res, Some(Action::TextChanged(text)) if text == "abc"));
+        // moving the cursor doesn't change the text
+        assert!(send(&mut scene, KeyEvent::pressed(Key::Left)).is_none());
+        let res = send(&mut scene, KeyEvent::pressed(Key::Return));
+        assert!(matches!(res, Some(Action::Submitted(text)) if text == "abc"));
+    }
+
+    #[test]
+    fn test_text_input_rtl_cursor() {
+        let bounds = Bounds::new(10, 0, 100, 20);
+        let mut state = TextInputState {
+            text: "añb".into(),
+            cursor: 3,
+        };
+        // 3 chars of 6 pixels, 5 pixels in from the edge the text starts at
+        assert_eq!(cursor_x(&bounds, &state, 6, Direction::Ltr), 10 + 5 + 18);
+        assert_eq!(cursor_x(&bounds, &state, 6, Direction::Rtl), 110 - 5);
+        state.cursor_home();
+        assert_eq!(cursor_x(&bounds, &state, 6, Direction::Ltr), 10 + 5);
+        assert_eq!(cursor_x(&bounds, &state, 6, Direction::Rtl), 110 - 5 - 18);
+    }
+}
@@ -0,0 +1,199 @@
+use crate::EventType;
+use crate::animation::FRAME_MS;
+use crate::scene::{EventResult, Scene, send_event};
+use crate::view::ViewId;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u32);
+
+#[derive(Debug)]
+pub(crate) struct Timer {
+    id: TimerId,
+    view: ViewId,
+    deadline: u64,
+    /// Time between firings of a repeating timer. `None` for a one-shot.
+    interval: Option<u64>,
+}
+
+impl<C> Scene<C> {
+    /// Ask for `EventType::Timer` to be sent to the view after `delay_ms`, and then every
+    /// `delay_ms` if `repeat` is set. Delays are measured from the last call to `tick`.
+    pub fn start_timer(&mut self, view: &ViewId, delay_ms: u64, repeat: bool) -> TimerId {
+        self.next_timer_id += 1;
+        let id = TimerId(self.next_timer_id);
+        self.timers.push(Timer {
+            id,
+            view: *view,
+            deadline: self.now_ms + delay_ms,
+            interval: if repeat { Some(delay_ms.max(1)) } else { None },
+        });
+        id
+    }
+    pub fn cancel_timer(&mut self, id: TimerId) {
+        self.timers.retain(|timer| timer.id != id);
+    }
+    pub fn cancel_timers_for_view(&mut self, view: &ViewId) {
+        self.timers.retain(|timer| &timer.view != view);
+    }
+    /// The time the next timer or animation frame is due. The app can sleep until then if
+    /// there is no input.
+    pub fn next_deadline(&self) -> Option<u64> {
+        let frame = if self.animations.is_empty() {
+            None
+        } else {
+            Some(self.now_ms + FRAME_MS)
+        };
+        self.timers
+            .iter()
+            .map(|timer| timer.deadline)
+            .chain(frame)
+            .min()
+    }
+    /// Advance the scene clock, step running animations and send `Timer` events to the views
+    /// whose timers are due, with `context` for their input handlers.
+    pub fn tick(&mut self, now_ms: u64, context: &mut C) -> Vec<EventResult> {
+        self.now_ms = now_ms;
+        let mut results = Vec::new();
+        for (id, view) in self.step_animations(now_ms) {
+            if let Some(action) = send_event(self, &view, EventType::AnimationDone(id), context) {
+                results.push((view, action));
+            }
+        }
+        let mut due: Vec<(u64, TimerId, ViewId)> = self
+            .timers
+            .iter()
+            .filter(|timer| timer.deadline <= now_ms)
+            .map(|timer| (timer.deadline, timer.id, timer.view))
+            .collect();
+        due.sort_by_key(|(deadline, _, _)| *deadline);
+        for (_, id, view) in due {
+            // an earlier handler in this tick may have cancelled it
+            let Some(index) = self.timers.iter().position(|timer| timer.id == id) else {
+                continue;
+            };
+            // reschedule a repeating timer before firing so its handler can cancel it
+            let timer = &mut self.timers[index];
+            match timer.interval {
+                Some(interval) => {
+                    // skip missed firings instead of sending a burst of them
+                    timer.deadline += ((now_ms - timer.deadline) / interval + 1) * interval;
+                }
+                None => {
+                    self.timers.remove(index);
+                }
+            }
+            if self.get_view(&view).is_none() {
+                continue;
+            }
+            if let Some(action) = send_event(self, &view, EventType::Timer(id), context) {
+                results.push((view, action));
+            }
+        }
+        results
+    }
+    /// The time passed to the last call to `tick`.
+    pub fn now_ms(&self) -> u64 {
+        self.now_ms
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scene::Scene;
+    use crate::timer::TimerId;
+    use crate::view::{View, ViewId};
+    use crate::{Action, EventType, Handler};
+
+    struct Count(i32);
+
+    fn make_counter(name: &ViewId) -> View {
+        View {
+            name: *name,
+            state: Some(alloc::boxed::Box::new(Count(0))),
+            input: Some(Handler::Fn(|e| {
+                if let EventType::Timer(_) = e.event_type
+                    && let Some(count) = e.scene.get_view_state::<Count>(e.target)
+                {
+                    count.0 += 1;
+                    return Some(Action::ValueChanged(count.0));
+                }
+                None
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_timers() {
+        let mut scene = Scene::new();
+        let once: ViewId = "once".into();
+        let every: ViewId = "every".into();
+        scene.add_view_to_root(make_counter(&once));
+        scene.add_view_to_root(make_counter(&every));
+        scene.tick(1000, &mut ());
+        scene.start_timer(&once, 50, false);
+        let repeating = scene.start_timer(&every, 100, true);
+        assert_eq!(scene.next_deadline(), Some(1050));
+
+        assert!(scene.tick(1049, &mut ()).is_empty());
+        let results = scene.tick(1050, &mut ());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], (id, Action::ValueChanged(1)) if id == once));
+        assert_eq!(scene.next_deadline(), Some(1100));
+
+        // a late tick only fires a repeating timer once
+        let results = scene.tick(1350, &mut ());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], (id, Action::ValueChanged(1)) if id == every));
+        assert_eq!(scene.next_deadline(), Some(1400));
+        scene.tick(1400, &mut ());
+        assert_eq!(scene.get_view_state::<Count>(&every).unwrap().0, 2);
+
+        scene.cancel_timer(repeating);
+        assert_eq!(scene.next_deadline(), None);
+        assert!(scene.tick(2000, &mut ()).is_empty());
+    }
+
+    #[test]
+    fn test_late_first_tick() {
+        let mut scene = Scene::new();
+        let every: ViewId = "every".into();
+        scene.add_view_to_root(make_counter(&every));
+        scene.start_timer(&every, 1, true);
+        // the clock jumps from 0 to a device uptime, which fires once and catches up at once
+        let results = scene.tick(u32::MAX as u64, &mut ());
+        assert_eq!(results.len(), 1);
+        assert_eq!(scene.next_deadline(), Some(u32::MAX as u64 + 1));
+    }
+
+    #[test]
+    fn test_cancel_in_same_tick() {
+        let mut scene = Scene::new();
+        let canceller: ViewId = "canceller".into();
+        let counter: ViewId = "counter".into();
+        scene.add_view_to_root(View {
+            name: canceller,
+            input: Some(Handler::Fn(|e| {
+                if let Some(id) = e.scene.get_view_state::<TimerId>(e.target).copied() {
+                    e.scene.cancel_timer(id);
+                }
+                Some(Action::Generic)
+            })),
+            ..Default::default()
+        });
+        scene.add_view_to_root(make_counter(&counter));
+        scene.start_timer(&canceller, 40, false);
+        let doomed = scene.start_timer(&counter, 50, true);
+        if let Some(view) = scene.get_view_mut(&canceller) {
+            view.state = Some(alloc::boxed::Box::new(doomed));
+        }
+
+        // both are due, but the first one cancels the second before it fires
+        let results = scene.tick(60, &mut ());
+        assert_eq!(results.len(), 1);
+        assert!(matches!(results[0], (id, Action::Generic) if id == canceller));
+        assert_eq!(scene.get_view_state::<Count>(&counter).unwrap().0, 0);
+        assert_eq!(scene.next_deadline(), None);
+    }
+}
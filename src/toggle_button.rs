@@ -1,17 +1,17 @@
-use crate::geom::Insets;
+use crate::geom::{Insets, Size};
 use crate::gfx::draw_centered_text;
 use crate::view::{View, ViewId};
-use crate::{Action, DrawEvent, GuiEvent, LayoutEvent, util};
+use crate::{Action, DrawEvent, GuiEvent, Handler, MeasureEvent, util};
 use alloc::boxed::Box;
 
-pub fn make_toggle_button(name: &ViewId, title: &str) -> View {
+pub fn make_toggle_button<C>(name: &ViewId, title: &str) -> View<C> {
     View {
         name: *name,
         title: title.into(),
         state: Some(Box::new(SelectedState::new())),
-        draw: Some(draw_toggle_button),
-        layout: Some(layout_toggle_button),
-        input: Some(input_toggle_button),
+        draw: Some(Handler::Fn(draw_toggle_button)),
+        measure: Some(Handler::Fn(measure_toggle_button)),
+        input: Some(Handler::Fn(input_toggle_button)),
         ..Default::default()
     }
 }
@@ -26,15 +26,16 @@ impl SelectedState {
     }
 }
 
-fn draw_toggle_button(e: &mut DrawEvent) {
+fn draw_toggle_button<C>(e: &mut DrawEvent<C>) {
+    let normal_bg = *e.button_bg();
     let (bg, fg) = if let Some(state) = e.view.get_state::<SelectedState>() {
         if state.selected {
             (&e.theme.selected_bg, &e.theme.selected_fg)
         } else {
-            (&e.theme.bg, &e.theme.fg)
+            (&normal_bg, &e.theme.fg)
         }
     } else {
-        (&e.theme.bg, &e.theme.fg)
+        (&normal_bg, &e.theme.fg)
     };
 
     e.ctx.fill_rect(&e.view.bounds, bg);
@@ -49,23 +50,27 @@ fn draw_toggle_button(e: &mut DrawEvent) {
     draw_centered_text(e.ctx, &e.view.title, &e.view.bounds, &e.theme.font, fg);
 }
 
-fn input_toggle_button(event: &mut GuiEvent) -> Option<Action> {
+fn input_toggle_button<C>(event: &mut GuiEvent<C>) -> Option<Action> {
     if let Some(state) = event.scene.get_view_state::<SelectedState>(event.target) {
         state.selected = !state.selected;
+        let selected = state.selected;
         event.scene.set_focused(event.target);
         event.scene.mark_dirty_view(event.target);
-        return Some(Action::Generic);
+        return Some(Action::Toggled(selected));
     }
     None
 }
 
-fn layout_toggle_button(event: &mut LayoutEvent) {
-    if let Some(view) = event.scene.get_view_mut(event.target) {
-        view.bounds = util::calc_bounds(view.bounds, event.theme.font, &view.title);
+fn measure_toggle_button<C>(event: &mut MeasureEvent<C>) -> Size {
+    match event.scene.get_view(event.target) {
+        Some(view) => util::calc_size(event.theme.font, &view.title),
+        None => event.space,
     }
 }
 
+#[cfg(test)]
 mod tests {
+    use crate::Action;
     use crate::geom::{Bounds, Point};
     use crate::scene::{Scene, click_at, draw_scene, layout_scene};
     use crate::test::MockDrawingContext;
@@ -100,7 +105,8 @@ mod tests {
             assert_eq!(state.selected, false);
         }
 
-        click_at(&mut scene, &vec![], Point::new(10, 10));
+        let res = click_at(&mut scene, &vec![], Point::new(10, 10), &mut ());
+        assert!(matches!(res, Some((_, Action::Toggled(true)))));
 
         {
             let state = scene
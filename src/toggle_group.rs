@@ -1,23 +1,23 @@
-use crate::geom::{Bounds, Point};
+use crate::geom::{Bounds, Point, Size};
 use crate::gfx::draw_centered_text;
 use crate::view::Flex::{Intrinsic, Resize};
-use crate::view::{View, ViewId};
-use crate::{Action, DrawEvent, EventType, GuiEvent, LayoutEvent};
+use crate::view::{Direction, View, ViewId};
+use crate::{Action, DrawEvent, EventType, GuiEvent, Handler, LayoutEvent, MeasureEvent};
 use alloc::boxed::Box;
 use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use core::any::Any;
 use core::option::Option::Some;
 
-pub fn make_toggle_group(name: &ViewId, data: Vec<&str>, selected: usize) -> View {
+pub fn make_toggle_group<C>(name: &ViewId, data: Vec<&str>, selected: usize) -> View<C> {
     View {
         name: *name,
         title: name.as_str().into(),
         bounds: Bounds::new(0, 0, (data.len() * 60) as i32, 30),
         state: Some(SelectOneOfState::new_with(data, selected)),
-        input: Some(input_toggle_group),
-        layout: Some(layout_toggle_group),
-        draw: Some(draw_toggle_group),
+        input: Some(Handler::Fn(input_toggle_group)),
+        measure: Some(Handler::Fn(measure_toggle_group)),
+        draw: Some(Handler::Fn(draw_toggle_group)),
         visible: true,
         h_flex: Resize,
         v_flex: Intrinsic,
@@ -39,20 +39,26 @@ impl SelectOneOfState {
     }
 }
 
-pub fn input_toggle_group(e: &mut GuiEvent) -> Option<Action> {
+pub fn input_toggle_group<C>(e: &mut GuiEvent<C>) -> Option<Action> {
     match &e.event_type {
         EventType::Tap(pt) => {
             e.scene.mark_dirty_view(e.target);
             e.scene.set_focused(e.target);
+            let rtl = e.scene.get_direction(e.target) == Direction::Rtl;
             if let Some(view) = e.scene.get_view_mut(e.target) {
                 let bounds = view.bounds;
                 if let Some(state) = view.get_state::<SelectOneOfState>() {
                     let cell_width = bounds.size.w / (state.items.len() as i32);
                     let x = pt.x - bounds.x();
-                    let n = x / cell_width;
-                    if n >= 0 && n < state.items.len() as i32 {
+                    let count = state.items.len() as i32;
+                    let n = if rtl {
+                        count - 1 - x / cell_width
+                    } else {
+                        x / cell_width
+                    };
+                    if n >= 0 && n < count {
                         state.selected = n as usize;
-                        return Some(Action::Command(state.items[state.selected].clone()));
+                        return Some(Action::Selected(state.selected));
                     }
                 }
             }
@@ -62,20 +68,26 @@ pub fn input_toggle_group(e: &mut GuiEvent) -> Option<Action> {
     None
 }
 
-fn draw_toggle_group(e: &mut DrawEvent) {
+fn draw_toggle_group<C>(e: &mut DrawEvent<C>) {
     let bounds = e.view.bounds;
-    e.ctx.fill_rect(&e.view.bounds, &e.theme.bg);
+    // the pointer state belongs to the whole group, so it shows on the unselected cells
+    let normal_bg = *e.button_bg();
+    e.ctx.fill_rect(&e.view.bounds, &normal_bg);
     let name = e.view.name.clone();
+    let rtl = e.direction == Direction::Rtl;
     if let Some(state) = e.view.get_state::<SelectOneOfState>() {
-        let cell_width = bounds.size.w / (state.items.len() as i32);
+        let count = state.items.len() as i32;
+        let cell_width = bounds.size.w / count;
         for (i, item) in state.items.iter().enumerate() {
+            // right to left groups put the first item in the rightmost cell
+            let slot = if rtl { count - 1 - i as i32 } else { i as i32 };
             let (bg, fg) = if i == state.selected {
                 (&e.theme.selected_bg, &e.theme.selected_fg)
             } else {
-                (&e.theme.bg, &e.theme.fg)
+                (&normal_bg, &e.theme.fg)
             };
             let bds = Bounds::new(
-                bounds.position.x + (i as i32) * cell_width + 1,
+                bounds.position.x + slot * cell_width + 1,
                 bounds.y(),
                 cell_width - 1,
                 bounds.h(),
@@ -93,9 +105,9 @@ fn draw_toggle_group(e: &mut DrawEvent) {
             // draw text
             draw_centered_text(e.ctx, item, &bds, &e.theme.font, fg);
 
-            // draw left edge except for the first one
-            if i != 0 {
-                let x = bounds.position.x + (i as i32) * cell_width;
+            // draw left edge except for the first cell
+            if slot != 0 {
+                let x = bounds.position.x + slot * cell_width;
                 e.ctx.line(
                     &Point::new(x, bounds.y()),
                     &Point::new(x, bounds.position.y + bounds.size.h - 1),
@@ -107,39 +119,57 @@ fn draw_toggle_group(e: &mut DrawEvent) {
     e.ctx.stroke_rect(&e.view.bounds, &e.theme.fg);
 }
 
-pub fn layout_toggle_group(pass: &mut LayoutEvent) {
-    if let Some(view) = pass.scene.get_view_mut(pass.target) {
-        let char_size = pass.theme.font.character_size;
-        let height = char_size.height + (char_size.height / 2) * 2; // padding
-        if view.h_flex == Resize {
-            view.bounds.size.w = pass.space.w;
-        }
-        if view.h_flex == Intrinsic {
-            if let Some(state) = view.get_state::<SelectOneOfState>() {
-                let mut width = 0;
-                for item in &state.items {
-                    width += char_size.width as i32;
-                    width += item.len() as i32 * char_size.width as i32;
-                    width += char_size.width as i32;
-                }
-                view.bounds.size.w = width;
-            }
-        }
-        if view.v_flex == Resize {
-            view.bounds.size.h = pass.space.h;
-        }
-        if view.v_flex == Intrinsic {
-            view.bounds.size.h = height as i32;
+pub fn measure_toggle_group<C>(e: &mut MeasureEvent<C>) -> Size {
+    let Some(view) = e.scene.get_view(e.target) else {
+        return e.space;
+    };
+    let char_size = e.theme.font.character_size;
+    let height = char_size.height + (char_size.height / 2) * 2; // padding
+    let mut size = view.bounds.size;
+    if view.h_flex == Resize {
+        size.w = e.space.w;
+    }
+    if view.h_flex == Intrinsic
+        && let Some(state) = view.get_state_ref::<SelectOneOfState>()
+    {
+        let mut width = 0;
+        for item in &state.items {
+            width += char_size.width as i32;
+            width += item.len() as i32 * char_size.width as i32;
+            width += char_size.width as i32;
         }
+        size.w = width;
+    }
+    if view.v_flex == Resize {
+        size.h = e.space.h;
+    }
+    if view.v_flex == Intrinsic {
+        size.h = height as i32;
+    }
+    size
+}
+
+/// For toggle groups built by hand with a layout handler. `make_toggle_group` measures.
+pub fn layout_toggle_group<C>(pass: &mut LayoutEvent<C>) {
+    let size = measure_toggle_group(&mut MeasureEvent {
+        scene: pass.scene,
+        target: pass.target,
+        space: pass.space,
+        theme: pass.theme,
+    });
+    if let Some(view) = pass.scene.get_view_mut(pass.target) {
+        view.bounds.size = size;
     }
     pass.layout_all_children(&pass.target.clone(), pass.space);
 }
+#[cfg(test)]
 mod tests {
+    use crate::Action;
     use crate::geom::{Bounds, Point};
     use crate::scene::{Scene, click_at, draw_scene, layout_scene};
     use crate::test::MockDrawingContext;
     use crate::toggle_group::{SelectOneOfState, make_toggle_group};
-    use crate::view::ViewId;
+    use crate::view::{Direction, ViewId};
     use alloc::vec;
 
     #[test]
@@ -161,7 +191,8 @@ mod tests {
             assert_eq!(state.selected, 0);
         }
 
-        click_at(&mut scene, &vec![], Point::new(50, 10));
+        let res = click_at(&mut scene, &vec![], Point::new(50, 10), &mut ());
+        assert!(matches!(res, Some((_, Action::Selected(1)))));
 
         {
             let state = &mut scene
@@ -175,5 +206,12 @@ mod tests {
         assert_eq!(scene.dirty, true);
         draw_scene(&mut scene, &mut ctx, &theme);
         assert_eq!(scene.dirty, false);
+
+        // right to left puts the first item on the right
+        scene.set_direction(Direction::Rtl);
+        let res = click_at(&mut scene, &vec![], Point::new(10, 10), &mut ());
+        assert!(matches!(res, Some((_, Action::Selected(2)))));
+        let res = click_at(&mut scene, &vec![], Point::new(90, 10), &mut ());
+        assert!(matches!(res, Some((_, Action::Selected(0)))));
     }
 }
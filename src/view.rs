@@ -1,5 +1,5 @@
-use crate::geom::{Bounds, Insets};
-use crate::{DrawFn, InputFn, LayoutFn};
+use crate::geom::{Bounds, Insets, Point, Size};
+use crate::{DrawHandler, InputHandler, LayoutHandler, LifecycleHandler, MeasureHandler};
 use alloc::boxed::Box;
 use alloc::string::String;
 use core::any::Any;
@@ -32,38 +32,201 @@ pub enum Align {
     Center,
     End,
 }
+impl Align {
+    /// The horizontal alignment to use in `direction`: `Start` and `End` swap for right to left.
+    pub fn for_direction(self, direction: Direction) -> Align {
+        match (self, direction) {
+            (Align::Start, Direction::Rtl) => Align::End,
+            (Align::End, Direction::Rtl) => Align::Start,
+            (align, _) => align,
+        }
+    }
+}
+
+/// Which way horizontal layouts and text run.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    /// Right to left: `Start` and `End` swap horizontally and rows are mirrored.
+    Rtl,
+}
 
+/// A node in the scene. `C` is the type of the app context that input handlers get, the
+/// same as the scene's.
 #[derive(Debug)]
-pub struct View {
+pub struct View<C = ()> {
     pub name: ViewId,
     pub title: String,
     pub bounds: Bounds,
     pub padding: Insets,
+    /// Space kept clear around the view by the layout of its parent.
+    pub margin: Insets,
+    /// Layouts never make the view smaller than this.
+    pub min_size: Option<Size>,
+    /// Layouts never make the view bigger than this.
+    pub max_size: Option<Size>,
 
     pub v_flex: Flex,
     pub h_flex: Flex,
     pub h_align: Align,
     pub v_align: Align,
+    /// The share of leftover space a `Resize` child gets in a box layout, relative to its
+    /// siblings. Defaults to 1.
+    pub flex_grow: u32,
+    /// How much an `Intrinsic` child gives up, relative to its siblings, when a box layout
+    /// overflows. Defaults to 0, so children keep their size.
+    pub flex_shrink: u32,
+    /// Size the view to this percentage of the width its parent offers, margin included,
+    /// instead of by its content. Leave `h_flex` as `Intrinsic` so box layouts don't also
+    /// give it a share of the leftover space.
+    pub h_percent: Option<u32>,
+    /// The same as `h_percent`, for the height.
+    pub v_percent: Option<u32>,
+    /// Keep the width to height ratio, given as a width and a height. The axis the parent
+    /// doesn't size follows the other one, so by default the height follows the width.
+    pub aspect_ratio: Option<(u32, u32)>,
+    /// How far the children are scrolled, moving them up and to the left when drawn and picked.
+    pub scroll_offset: Point,
+    /// Keep the children from drawing or being picked outside the padded box.
+    pub clip_children: bool,
+    /// The direction of this view and its children. `None` uses the parent's direction.
+    pub direction: Option<Direction>,
 
     pub visible: bool,
-    pub input: Option<InputFn>,
+    /// A disabled view and its children don't receive input and are drawn in disabled colors.
+    pub enabled: bool,
+    pub input: Option<InputHandler<C>>,
     pub state: Option<Box<dyn Any>>,
-    pub layout: Option<LayoutFn>,
-    pub draw: Option<DrawFn>,
+    pub layout: Option<LayoutHandler<C>>,
+    /// Reports the size the view wants for some space, without changing anything. Views with
+    /// only a measure handler are sized by it when they are laid out.
+    pub measure: Option<MeasureHandler<C>>,
+    pub draw: Option<DrawHandler<C>>,
+    /// Called when the view is mounted, unmounted, shown, hidden, focused or unfocused.
+    pub lifecycle: Option<LifecycleHandler<C>>,
 }
 
-impl View {
-    pub fn position_at(mut self, x: i32, y: i32) -> View {
+impl<C> View<C> {
+    pub fn position_at(mut self, x: i32, y: i32) -> View<C> {
         self.bounds.position.x = x;
         self.bounds.position.y = y;
         self
     }
-    pub fn with_size(mut self, w: i32, h: i32) -> View {
+    pub fn with_size(mut self, w: i32, h: i32) -> View<C> {
         self.bounds.size.w = w;
         self.bounds.size.h = h;
         self
     }
-    pub fn hide(mut self) -> View {
+    pub fn with_margin(mut self, margin: Insets) -> View<C> {
+        self.margin = margin;
+        self
+    }
+    pub fn with_min_size(mut self, w: i32, h: i32) -> View<C> {
+        self.min_size = Some(Size::new(w, h));
+        self
+    }
+    pub fn with_max_size(mut self, w: i32, h: i32) -> View<C> {
+        self.max_size = Some(Size::new(w, h));
+        self
+    }
+    /// The size including the margin.
+    pub fn outer_size(&self) -> Size {
+        self.bounds.size + self.margin
+    }
+    /// The space left for the view itself out of the space its parent offers: the view's
+    /// percentage of it, inside the margin and no bigger than the max size.
+    pub fn available_space(&self, space: Size) -> Size {
+        let mut space = space;
+        if let Some(percent) = self.h_percent {
+            space.w = space.w * percent as i32 / 100;
+        }
+        if let Some(percent) = self.v_percent {
+            space.h = space.h * percent as i32 / 100;
+        }
+        let mut space = space - self.margin;
+        if let Some(max) = self.max_size {
+            space.w = space.w.min(max.w);
+            space.h = space.h.min(max.h);
+        }
+        space
+    }
+    /// Apply the percentage and aspect ratio sizes to the size the view came up with for
+    /// `space`, which is from `available_space`.
+    pub fn constrain_size(&self, size: Size, space: Size) -> Size {
+        let mut size = size;
+        if self.h_percent.is_some() {
+            size.w = space.w;
+        }
+        if self.v_percent.is_some() {
+            size.h = space.h;
+        }
+        if let Some((w, h)) = self.aspect_ratio
+            && w > 0
+            && h > 0
+        {
+            let width_set = self.h_percent.is_some() || self.h_flex == Flex::Resize;
+            let height_set = self.v_percent.is_some() || self.v_flex == Flex::Resize;
+            if height_set && !width_set {
+                size.w = size.h * w as i32 / h as i32;
+            } else {
+                size.h = size.w * h as i32 / w as i32;
+            }
+        }
+        size
+    }
+    /// The size of the view for `space` when its content, without the padding, measures
+    /// `content`: the space on `Resize` axes, else the content plus the padding.
+    pub fn resolve_size(&self, space: Size, content: Size) -> Size {
+        let content = content + self.padding;
+        Size::new(
+            if self.h_flex == Flex::Resize {
+                space.w
+            } else {
+                content.w
+            },
+            if self.v_flex == Flex::Resize {
+                space.h
+            } else {
+                content.h
+            },
+        )
+    }
+    /// Limit a size to the min and max size. The min size wins if they conflict.
+    pub fn clamp_size(&self, size: Size) -> Size {
+        let mut size = size;
+        if let Some(max) = self.max_size {
+            size.w = size.w.min(max.w);
+            size.h = size.h.min(max.h);
+        }
+        if let Some(min) = self.min_size {
+            size.w = size.w.max(min.w);
+            size.h = size.h.max(min.h);
+        }
+        size
+    }
+    pub fn with_flex_grow(mut self, grow: u32) -> View<C> {
+        self.flex_grow = grow;
+        self
+    }
+    pub fn with_flex_shrink(mut self, shrink: u32) -> View<C> {
+        self.flex_shrink = shrink;
+        self
+    }
+    pub fn with_direction(mut self, direction: Direction) -> View<C> {
+        self.direction = Some(direction);
+        self
+    }
+    pub fn with_percent_size(mut self, width: Option<u32>, height: Option<u32>) -> View<C> {
+        self.h_percent = width;
+        self.v_percent = height;
+        self
+    }
+    pub fn with_aspect_ratio(mut self, w: u32, h: u32) -> View<C> {
+        self.aspect_ratio = Some((w, h));
+        self
+    }
+    pub fn hide(mut self) -> View<C> {
         self.visible = false;
         self
     }
@@ -73,9 +236,12 @@ impl View {
         }
         None
     }
+    pub fn get_state_ref<T: 'static>(&self) -> Option<&T> {
+        self.state.as_ref()?.downcast_ref::<T>()
+    }
 }
 
-impl Default for View {
+impl<C> Default for View<C> {
     fn default() -> Self {
         let id: ViewId = ViewId::new("noname");
         View {
@@ -83,17 +249,31 @@ impl Default for View {
             title: id.as_str().into(),
             bounds: Default::default(),
             padding: Default::default(),
+            margin: Default::default(),
+            min_size: None,
+            max_size: None,
 
             h_flex: Flex::Intrinsic,
             v_flex: Flex::Intrinsic,
             h_align: Align::Center,
             v_align: Align::Center,
+            flex_grow: 1,
+            flex_shrink: 0,
+            h_percent: None,
+            v_percent: None,
+            aspect_ratio: None,
+            scroll_offset: Point::zero(),
+            clip_children: false,
+            direction: None,
 
             visible: true,
+            enabled: true,
             input: None,
             state: None,
             layout: None,
+            measure: None,
             draw: None,
+            lifecycle: None,
         }
     }
 }